@@ -43,7 +43,7 @@ impl LogFormatter for MyLogFormatter {
     fn min_width(&self) -> u16 {
         4
     }
-    fn format(&self, _width: usize, evt: &ExtLogRecord) -> Vec<Line> {
+    fn format(&self, _width: usize, evt: &ExtLogRecord) -> Vec<Line<'_>> {
         let mut lines = vec![];
         match evt.level {
             log::Level::Error => {
@@ -165,35 +165,34 @@ impl App {
 
     fn handle_ui_event(&mut self, event: Event) {
         debug!(target: "App", "Handling UI event: {:?}",event);
-        let state = self.selected_state();
 
-        if let Event::Key(key) = event {
+        if let Event::Key(key) = &event {
             #[cfg(feature = "crossterm")]
             let code = key.code;
 
             #[cfg(feature = "termion")]
-            let code = key;
-
-            match code.into() {
-                Key::Char('q') => self.mode = AppMode::Quit,
-                Key::Char('\t') => self.next_tab(),
+            let code = *key;
+
+            match code {
+                Key::Char('q') => {
+                    self.mode = AppMode::Quit;
+                    return;
+                }
+                Key::Char('\t') => {
+                    self.next_tab();
+                    return;
+                }
                 #[cfg(feature = "crossterm")]
-                Key::Tab => self.next_tab(),
-                Key::Char(' ') => state.transition(TuiWidgetEvent::SpaceKey),
-                Key::Esc => state.transition(TuiWidgetEvent::EscapeKey),
-                Key::PageUp => state.transition(TuiWidgetEvent::PrevPageKey),
-                Key::PageDown => state.transition(TuiWidgetEvent::NextPageKey),
-                Key::Up => state.transition(TuiWidgetEvent::UpKey),
-                Key::Down => state.transition(TuiWidgetEvent::DownKey),
-                Key::Left => state.transition(TuiWidgetEvent::LeftKey),
-                Key::Right => state.transition(TuiWidgetEvent::RightKey),
-                Key::Char('+') => state.transition(TuiWidgetEvent::PlusKey),
-                Key::Char('-') => state.transition(TuiWidgetEvent::MinusKey),
-                Key::Char('h') => state.transition(TuiWidgetEvent::HideKey),
-                Key::Char('f') => state.transition(TuiWidgetEvent::FocusKey),
+                Key::Tab => {
+                    self.next_tab();
+                    return;
+                }
                 _ => (),
             }
         }
+        if let Some(widget_event) = to_widget_event(&event) {
+            self.selected_state().transition(widget_event);
+        }
     }
 
     fn selected_state(&mut self) -> &mut TuiWidgetState {
@@ -258,7 +257,7 @@ impl Widget for &mut App {
             Constraint::Fill(50),
             Constraint::Fill(30),
             Constraint::Length(progress_height),
-            Constraint::Length(3),
+            Constraint::Length(4),
         ])
         .areas(area);
         // show two TuiWidgetState side-by-side
@@ -270,7 +269,7 @@ impl Widget for &mut App {
             .select(self.selected_tab)
             .render(tabs_area, buf);
 
-        TuiLoggerSmartWidget::default()
+        let smart_widget = TuiLoggerSmartWidget::default()
             .style_error(Style::default().fg(Color::Red))
             .style_debug(Style::default().fg(Color::Green))
             .style_warn(Style::default().fg(Color::Yellow))
@@ -282,12 +281,13 @@ impl Widget for &mut App {
             .output_target(true)
             .output_file(true)
             .output_line(true)
-            .state(self.selected_state())
-            .render(smart_area, buf);
+            .follow_indicator(true);
+        // The logger widgets implement both Widget and StatefulWidget
+        StatefulWidget::render(smart_widget, smart_area, buf, self.selected_state());
 
         // An example of filtering the log output. The left TuiLoggerWidget is filtered to only show
         // log entries for the "App" target. The right TuiLoggerWidget shows all log entries.
-        let filter_state = TuiWidgetState::new()
+        let mut filter_state = TuiWidgetState::new()
             .set_default_display_level(LevelFilter::Off)
             .set_level_for_target("App", LevelFilter::Debug)
             .set_level_for_target("background-task", LevelFilter::Info);
@@ -296,7 +296,7 @@ impl Widget for &mut App {
             formatter = Some(Box::new(MyLogFormatter {}));
         }
 
-        TuiLoggerWidget::default()
+        let filtered_widget = TuiLoggerWidget::default()
             .block(Block::bordered().title("Filtered TuiLoggerWidget"))
             .output_separator('|')
             .output_timestamp(Some("%F %H:%M:%S%.3f".to_string()))
@@ -304,11 +304,10 @@ impl Widget for &mut App {
             .output_target(false)
             .output_file(false)
             .output_line(false)
-            .style(Style::default().fg(Color::White))
-            .state(&filter_state)
-            .render(left, buf);
+            .style(Style::default().fg(Color::White));
+        StatefulWidget::render(filtered_widget, left, buf, &mut filter_state);
 
-        TuiLoggerWidget::default()
+        let unfiltered_widget = TuiLoggerWidget::default()
             .block(Block::bordered().title("Unfiltered TuiLoggerWidget"))
             .opt_formatter(formatter)
             .output_separator('|')
@@ -317,8 +316,8 @@ impl Widget for &mut App {
             .output_target(false)
             .output_file(false)
             .output_line(false)
-            .style(Style::default().fg(Color::White))
-            .render(right, buf);
+            .style(Style::default().fg(Color::White));
+        Widget::render(unfiltered_widget, right, buf);
 
         if let Some(percent) = self.progress_counter {
             Gauge::default()
@@ -330,8 +329,10 @@ impl Widget for &mut App {
         if area.width > 40 {
             Text::from(vec![
                 "Q: Quit | Tab: Switch state | ↑/↓: Select target | f: Focus target".into(),
-                "←/→: Display level | +/-: Filter level | Space: Toggle hidden targets".into(),
-                "h: Hide target selector | PageUp/Down: Scroll | Esc: Cancel scroll".into(),
+                "←/→/1-5: Display level | +/-: Filter level | Space: Toggle hidden targets".into(),
+                "h: Hide target selector | PageUp/Down/Home/End: Scroll | Esc: Cancel scroll"
+                    .into(),
+                "</>: Display level of all targets | p: Pause view | c: Clear log history | r: Reset levels | w: Wrap".into(),
             ])
             .style(Color::Gray)
             .centered()
@@ -365,6 +366,11 @@ mod crossterm_backend {
         execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)
     }
 
+    /// Convert the event with the default key map of tui-logger
+    pub fn to_widget_event(event: &Event) -> Option<TuiWidgetEvent> {
+        tui_logger::event::from_crossterm(event)
+    }
+
     pub fn input_thread(tx_event: mpsc::Sender<AppEvent>) -> anyhow::Result<()> {
         trace!(target:"crossterm", "Starting input thread");
         while let Ok(event) = event::read() {
@@ -400,6 +406,11 @@ mod termion_backend {
         Ok(())
     }
 
+    /// Convert the event with the default key map of tui-logger
+    pub fn to_widget_event(event: &Event) -> Option<TuiWidgetEvent> {
+        tui_logger::event::from_termion(event)
+    }
+
     pub fn input_thread(tx_event: mpsc::Sender<AppEvent>) -> anyhow::Result<()> {
         trace!(target:"termion", "Starting input thread");
         for event in io::stdin().events() {
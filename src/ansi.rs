@@ -0,0 +1,285 @@
+//! Handling of ANSI escape sequences embedded in log messages.
+//!
+//! SGR sequences (`ESC [ ... m`) are converted into ratatui styles.
+//! All other escape sequences are skipped.
+//! For colored output to files and streams, styles are converted back into SGR sequences.
+use std::iter::Peekable;
+use std::str::Chars;
+
+use ratatui::style::{Color, Modifier, Style};
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+
+/// Consume an escape sequence following ESC.
+/// Returns the parameters, if the sequence is an SGR sequence.
+fn consume_escape(chars: &mut Peekable<Chars>) -> Option<String> {
+    match chars.next() {
+        Some('[') => {
+            // CSI: parameter and intermediate bytes up to the final byte
+            let mut params = String::new();
+            for ch in chars.by_ref() {
+                if ('\x40'..='\x7e').contains(&ch) {
+                    return if ch == 'm' { Some(params) } else { None };
+                }
+                params.push(ch);
+            }
+            None
+        }
+        Some(']') => {
+            // OSC: terminated by BEL or ESC \
+            while let Some(ch) = chars.next() {
+                if ch == BEL {
+                    break;
+                }
+                if ch == ESC {
+                    if chars.peek() == Some(&'\\') {
+                        chars.next();
+                    }
+                    break;
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+fn ansi_color(code: u16) -> Color {
+    match code {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        7 => Color::Gray,
+        8 => Color::DarkGray,
+        9 => Color::LightRed,
+        10 => Color::LightGreen,
+        11 => Color::LightYellow,
+        12 => Color::LightBlue,
+        13 => Color::LightMagenta,
+        14 => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+/// Parse the extended color of SGR 38/48: `5;n` or `2;r;g;b`
+fn extended_color(codes: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    match codes.next()? {
+        5 => Some(Color::Indexed(codes.next()? as u8)),
+        2 => {
+            let r = codes.next()? as u8;
+            let g = codes.next()? as u8;
+            let b = codes.next()? as u8;
+            Some(Color::Rgb(r, g, b))
+        }
+        _ => None,
+    }
+}
+
+fn apply_sgr(params: &str, style: Style, base: Style) -> Style {
+    let mut style = style;
+    let mut codes = params
+        .split([';', ':'])
+        .map(|code| code.parse::<u16>().unwrap_or(0));
+    while let Some(code) = codes.next() {
+        style = match code {
+            0 => base,
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            5 => style.add_modifier(Modifier::SLOW_BLINK),
+            6 => style.add_modifier(Modifier::RAPID_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            8 => style.add_modifier(Modifier::HIDDEN),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style.remove_modifier(Modifier::ITALIC),
+            24 => style.remove_modifier(Modifier::UNDERLINED),
+            25 => style.remove_modifier(Modifier::SLOW_BLINK | Modifier::RAPID_BLINK),
+            27 => style.remove_modifier(Modifier::REVERSED),
+            28 => style.remove_modifier(Modifier::HIDDEN),
+            29 => style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg(ansi_color(code - 30)),
+            38 => match extended_color(&mut codes) {
+                Some(color) => style.fg(color),
+                None => style,
+            },
+            39 => Style {
+                fg: base.fg,
+                ..style
+            },
+            40..=47 => style.bg(ansi_color(code - 40)),
+            48 => match extended_color(&mut codes) {
+                Some(color) => style.bg(color),
+                None => style,
+            },
+            49 => Style {
+                bg: base.bg,
+                ..style
+            },
+            90..=97 => style.fg(ansi_color(code - 90 + 8)),
+            100..=107 => style.bg(ansi_color(code - 100 + 8)),
+            _ => style,
+        };
+    }
+    style
+}
+
+/// Split the text into characters with their style as defined by the SGR sequences.
+/// The base style is used for the text before the first SGR sequence and on reset.
+pub(crate) fn parse_ansi(text: &str, base: Style) -> Vec<(char, Style)> {
+    let mut styled = Vec::with_capacity(text.len());
+    let mut style = base;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == ESC {
+            if let Some(params) = consume_escape(&mut chars) {
+                style = apply_sgr(&params, style, base);
+            }
+        } else {
+            styled.push((ch, style));
+        }
+    }
+    styled
+}
+
+fn sgr_color(color: Color, base: u16) -> String {
+    match color {
+        Color::Reset => format!("{}", base + 9),
+        Color::Black => format!("{}", base),
+        Color::Red => format!("{}", base + 1),
+        Color::Green => format!("{}", base + 2),
+        Color::Yellow => format!("{}", base + 3),
+        Color::Blue => format!("{}", base + 4),
+        Color::Magenta => format!("{}", base + 5),
+        Color::Cyan => format!("{}", base + 6),
+        Color::Gray => format!("{}", base + 7),
+        Color::DarkGray => format!("{}", base + 60),
+        Color::LightRed => format!("{}", base + 61),
+        Color::LightGreen => format!("{}", base + 62),
+        Color::LightYellow => format!("{}", base + 63),
+        Color::LightBlue => format!("{}", base + 64),
+        Color::LightMagenta => format!("{}", base + 65),
+        Color::LightCyan => format!("{}", base + 66),
+        Color::White => format!("{}", base + 67),
+        Color::Indexed(i) => format!("{};5;{}", base + 8, i),
+        Color::Rgb(r, g, b) => format!("{};2;{};{};{}", base + 8, r, g, b),
+    }
+}
+
+/// Convert the style into an SGR sequence. Returns an empty string for the default style.
+pub(crate) fn to_sgr(style: Style) -> String {
+    const MODIFIERS: [(Modifier, &str); 9] = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::RAPID_BLINK, "6"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::HIDDEN, "8"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+    let mut codes: Vec<String> = MODIFIERS
+        .iter()
+        .filter(|(modifier, _)| style.add_modifier.contains(*modifier))
+        .map(|(_, code)| code.to_string())
+        .collect();
+    if let Some(fg) = style.fg {
+        codes.push(sgr_color(fg, 30));
+    }
+    if let Some(bg) = style.bg {
+        codes.push(sgr_color(bg, 40));
+    }
+    if codes.is_empty() {
+        String::new()
+    } else {
+        format!("{}[{}m", ESC, codes.join(";"))
+    }
+}
+
+/// Remove all escape sequences from the text.
+pub(crate) fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == ESC {
+            consume_escape(&mut chars);
+        } else {
+            stripped.push(ch);
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain() {
+        let base = Style::default().fg(Color::Blue);
+        let styled = parse_ansi("ab", base);
+        assert_eq!(styled, vec![('a', base), ('b', base)]);
+    }
+    #[test]
+    fn test_parse_sgr() {
+        let base = Style::default();
+        let styled = parse_ansi("\x1b[1;31ma\x1b[39mb\x1b[0mc", base);
+        let bold_red = base.fg(Color::Red).add_modifier(Modifier::BOLD);
+        assert_eq!(
+            styled,
+            vec![
+                ('a', bold_red),
+                (
+                    'b',
+                    Style {
+                        fg: None,
+                        ..bold_red
+                    }
+                ),
+                ('c', base)
+            ]
+        );
+    }
+    #[test]
+    fn test_parse_extended_colors() {
+        let base = Style::default();
+        let styled = parse_ansi("\x1b[38;5;208ma\x1b[48;2;1;2;3mb", base);
+        let orange = base.fg(Color::Indexed(208));
+        assert_eq!(
+            styled,
+            vec![('a', orange), ('b', orange.bg(Color::Rgb(1, 2, 3)))]
+        );
+    }
+    #[test]
+    fn test_skip_other_sequences() {
+        let base = Style::default();
+        let styled = parse_ansi("\x1b[2Ka\x1b]0;title\x07b", base);
+        assert_eq!(styled, vec![('a', base), ('b', base)]);
+    }
+    #[test]
+    fn test_to_sgr() {
+        let base = Style::default();
+        assert_eq!(to_sgr(base), "");
+        let style = base
+            .fg(Color::LightRed)
+            .bg(Color::Rgb(1, 2, 3))
+            .add_modifier(Modifier::BOLD);
+        assert_eq!(to_sgr(style), "\x1b[1;91;48;2;1;2;3m");
+        assert_eq!(
+            parse_ansi(&format!("{}a", to_sgr(style)), base),
+            vec![('a', style)]
+        );
+    }
+    #[test]
+    fn test_strip() {
+        assert_eq!(strip_ansi("\x1b[1;31mred\x1b[0m text\x1b[2K"), "red text");
+        assert_eq!(strip_ansi("plain äöü"), "plain äöü");
+    }
+}
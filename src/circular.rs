@@ -87,6 +87,21 @@ impl<T> CircularBuffer<T> {
         }
         self.next_write_pos += 1;
     }
+    /// Same as push(), but returns the overwritten element
+    pub fn push_evict(&mut self, elem: T) -> Option<T> {
+        let max_depth = self.buffer.capacity();
+        let evicted = if self.buffer.len() < max_depth {
+            self.buffer.push(elem);
+            None
+        } else {
+            Some(std::mem::replace(
+                &mut self.buffer[self.next_write_pos % max_depth],
+                elem,
+            ))
+        };
+        self.next_write_pos += 1;
+        evicted
+    }
     /// Take out all elements from the buffer, leaving an empty buffer behind
     pub fn take(&mut self) -> Vec<T> {
         let mut consumed = vec![];
@@ -102,6 +117,25 @@ impl<T> CircularBuffer<T> {
         self.next_write_pos = 0;
         consumed
     }
+    /// Remove all elements from the buffer and reset the total number of elements.
+    /// The capacity is kept.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.next_write_pos = 0;
+    }
+    /// Keep only the elements, for which the predicate returns true.
+    /// The remaining elements are compacted in their original order.
+    /// Afterwards the total number of elements equals the number of retained elements.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        for elem in self.take() {
+            if f(&elem) {
+                self.push(elem);
+            }
+        }
+    }
     /// Total number of elements pushed into the buffer.
     pub fn total_elements(&self) -> usize {
         self.next_write_pos
@@ -112,7 +146,7 @@ impl<T> CircularBuffer<T> {
     }
     /// Return an iterator to step through all elements in the sequence,
     /// as these have been pushed (FIFO)
-    pub fn iter(&mut self) -> iter::Chain<std::slice::Iter<T>, std::slice::Iter<T>> {
+    pub fn iter(&mut self) -> iter::Chain<std::slice::Iter<'_, T>, std::slice::Iter<'_, T>> {
         let max_depth = self.buffer.capacity();
         if self.next_write_pos <= max_depth {
             // If buffer is not completely filled, then just iterate through it
@@ -128,7 +162,8 @@ impl<T> CircularBuffer<T> {
     /// as these have been pushed (LIFO)
     pub fn rev_iter(
         &mut self,
-    ) -> iter::Chain<std::iter::Rev<std::slice::Iter<T>>, std::iter::Rev<std::slice::Iter<T>>> {
+    ) -> iter::Chain<std::iter::Rev<std::slice::Iter<'_, T>>, std::iter::Rev<std::slice::Iter<'_, T>>>
+    {
         let max_depth = self.buffer.capacity();
         if self.next_write_pos <= max_depth {
             // If buffer is not completely filled, then just iterate through it
@@ -440,6 +475,45 @@ mod tests {
         }
     }
     #[test]
+    fn clear() {
+        use crate::CircularBuffer;
+
+        let mut cb: CircularBuffer<u64> = CircularBuffer::new(5);
+        for i in 1..8 {
+            cb.push(i);
+        }
+        cb.clear();
+        assert_eq!(0, cb.len());
+        assert_eq!(0, cb.total_elements());
+        assert_eq!(5, cb.capacity());
+        cb.push(1);
+        cb.push(2);
+        assert_eq!(vec![1, 2], cb.take());
+    }
+    #[test]
+    fn retain() {
+        use crate::CircularBuffer;
+
+        let mut cb: CircularBuffer<u64> = CircularBuffer::new(5);
+        for i in 1..9 {
+            cb.push(i);
+        }
+        cb.retain(|&i| i % 2 == 0);
+        assert_eq!(5, cb.capacity());
+        assert_eq!(3, cb.total_elements());
+        {
+            let mut cb_iter = cb.iter();
+            assert_eq!(cb_iter.next(), Some(&4));
+            assert_eq!(cb_iter.next(), Some(&6));
+            assert_eq!(cb_iter.next(), Some(&8));
+            assert_eq!(cb_iter.next(), None);
+        }
+        for i in 9..13 {
+            cb.push(i);
+        }
+        assert_eq!(vec![8, 9, 10, 11, 12], cb.take());
+    }
+    #[test]
     fn take() {
         use crate::CircularBuffer;
 
@@ -0,0 +1,54 @@
+//! Ingestion of decoded defmt frames, e.g. from a probe-rs front-end.
+//!
+//! The frames are decoded by the host application (e.g. with `defmt-decoder`),
+//! so this module does not depend on any defmt crate.
+use log::Level;
+
+use crate::{ExtLogRecord, TUI_LOGGER};
+
+/// Target used for frames without module path
+pub const DEFMT_TARGET: &str = "defmt";
+
+/// A decoded defmt frame
+#[derive(Debug, Clone, Default)]
+pub struct DefmtFrame<'a> {
+    /// Level of the frame. `None` for `println!()` frames, which are logged as `Info`.
+    pub level: Option<Level>,
+    /// Timestamp of the firmware as formatted by the decoder, e.g. `1.234567`.
+    /// It is stored as key-value pair `device_time`.
+    pub timestamp: Option<&'a str>,
+    /// The formatted message
+    pub message: &'a str,
+    /// Source file of the firmware
+    pub file: Option<&'a str>,
+    /// Source line of the firmware
+    pub line: Option<u32>,
+    /// Module path of the firmware, which is used as target
+    pub module_path: Option<&'a str>,
+}
+
+impl DefmtFrame<'_> {
+    fn to_record(&self) -> ExtLogRecord {
+        let level = self.level.unwrap_or(Level::Info);
+        let target = self.module_path.unwrap_or(DEFMT_TARGET);
+        let now = TUI_LOGGER.now();
+        let mut record = ExtLogRecord::new(now, level, target, self.message);
+        if let Some(file) = self.file {
+            record = record.location(file, self.line.unwrap_or(0));
+        }
+        if let Some(timestamp) = self.timestamp {
+            record = record.key_value("device_time", timestamp);
+        }
+        record
+    }
+}
+
+/// Feed a decoded defmt frame into the buffer.
+/// The timestamp of the record is the time of reception on the host.
+/// The log levels are checked as for events of the log crate.
+pub fn push_defmt_frame(frame: &DefmtFrame) {
+    let record = frame.to_record();
+    if TUI_LOGGER.level_enabled(record.level, &record.target) {
+        crate::push_record(record);
+    }
+}
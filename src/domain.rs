@@ -0,0 +1,92 @@
+//! Log domains: Events of selected targets are routed into separate buffers,
+//! e.g. to show "ui::*" in one pane and "net::*" in another one.
+use crate::levels::is_below;
+use crate::store::EventStore;
+use crate::{LogSink, TUI_LOGGER};
+
+pub struct LogDomain {
+    pub name: String,
+    /// Target prefixes without the optional "::*"
+    prefixes: Vec<String>,
+    pub events: EventStore,
+    /// Receives the events of the domain in addition to the global sinks
+    pub sink: Option<Box<dyn LogSink>>,
+}
+
+impl LogDomain {
+    /// A prefix matches the target itself and all targets below it,
+    /// e.g. "net" matches "net" and "net::http", but not "network".
+    pub fn matches(&self, target: &str) -> bool {
+        self.prefixes.iter().any(|prefix| is_below(prefix, target))
+    }
+}
+
+/// Route the events of the targets matching one of the prefixes, e.g. `"net::*"` or `"net"`,
+/// into the domain's own buffer of the given depth instead of the main buffer.
+/// The domain is shown by a widget configured with `TuiLoggerWidget::domain()`.
+///
+/// If the domain exists, its prefixes and depth are replaced and its events are removed.
+/// An event matching several domains goes to the first one set.
+/// The global sinks still receive the events of all domains.
+pub fn set_log_domain(name: &str, prefixes: &[&str], depth: usize) {
+    let prefixes = prefixes
+        .iter()
+        .map(|prefix| prefix.strip_suffix("::*").unwrap_or(prefix).to_string())
+        .collect();
+    let mut tli = TUI_LOGGER.inner.lock();
+    match tli.domains.iter_mut().find(|domain| domain.name == name) {
+        Some(domain) => {
+            domain.prefixes = prefixes;
+            domain.events = EventStore::new(depth);
+        }
+        None => tli.domains.push(LogDomain {
+            name: name.to_string(),
+            prefixes,
+            events: EventStore::new(depth),
+            sink: None,
+        }),
+    }
+}
+
+/// Write the events of the domain additionally to the sink, e.g. a
+/// [`TuiLoggerFile`](crate::TuiLoggerFile) per domain. A previous sink of the domain is replaced.
+/// Returns false, if the domain does not exist.
+pub fn set_log_domain_sink(name: &str, sink: impl LogSink + 'static) -> bool {
+    let mut tli = TUI_LOGGER.inner.lock();
+    match tli.domains.iter_mut().find(|domain| domain.name == name) {
+        Some(domain) => {
+            domain.sink = Some(Box::new(sink));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Remove the domain together with its events and sink. New events of its targets
+/// go to the main buffer again. Returns false, if the domain does not exist.
+pub fn remove_log_domain(name: &str) -> bool {
+    let mut tli = TUI_LOGGER.inner.lock();
+    let len = tli.domains.len();
+    tli.domains.retain(|domain| domain.name != name);
+    tli.domains.len() != len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_matches() {
+        let domain = LogDomain {
+            name: "net".to_string(),
+            prefixes: vec!["net".to_string(), "app::db".to_string()],
+            events: EventStore::new(1),
+            sink: None,
+        };
+        assert!(domain.matches("net"));
+        assert!(domain.matches("net::http"));
+        assert!(domain.matches("app::db::pool"));
+        assert!(!domain.matches("network"));
+        assert!(!domain.matches("app"));
+    }
+}
@@ -0,0 +1,67 @@
+//! Conversion of `crossterm` events into [TuiWidgetKey]s and [TuiWidgetEvent]s.
+//!
+//! The conversion into a [TuiWidgetEvent] uses the default key map.
+//! Usage example:
+//!```ignore
+//! let event = crossterm::event::read()?;
+//! if let Some(widget_event) = tui_logger::event::from_crossterm(&event) {
+//!     state.transition(widget_event);
+//! }
+//!```
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+use super::DEFAULT_KEYMAP;
+use crate::{TuiWidgetEvent, TuiWidgetKey};
+
+impl TryFrom<&KeyEvent> for TuiWidgetKey {
+    type Error = ();
+
+    /// Only key presses without modifiers (apart from shift) are converted.
+    fn try_from(key: &KeyEvent) -> Result<Self, Self::Error> {
+        if key.kind == KeyEventKind::Release {
+            return Err(());
+        }
+        if !(key.modifiers - KeyModifiers::SHIFT).is_empty() {
+            return Err(());
+        }
+        match key.code {
+            KeyCode::Char(ch) => Ok(TuiWidgetKey::Char(ch)),
+            KeyCode::Up => Ok(TuiWidgetKey::Up),
+            KeyCode::Down => Ok(TuiWidgetKey::Down),
+            KeyCode::Left => Ok(TuiWidgetKey::Left),
+            KeyCode::Right => Ok(TuiWidgetKey::Right),
+            KeyCode::PageUp => Ok(TuiWidgetKey::PageUp),
+            KeyCode::PageDown => Ok(TuiWidgetKey::PageDown),
+            KeyCode::Home => Ok(TuiWidgetKey::Home),
+            KeyCode::End => Ok(TuiWidgetKey::End),
+            KeyCode::Esc => Ok(TuiWidgetKey::Esc),
+            _ => Err(()),
+        }
+    }
+}
+
+impl TryFrom<&Event> for TuiWidgetKey {
+    type Error = ();
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        match event {
+            Event::Key(key) => TuiWidgetKey::try_from(key),
+            _ => Err(()),
+        }
+    }
+}
+
+impl TryFrom<&Event> for TuiWidgetEvent {
+    type Error = ();
+
+    /// Convert the event using the default key map.
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        let key = TuiWidgetKey::try_from(event)?;
+        DEFAULT_KEYMAP.get(key).ok_or(())
+    }
+}
+
+/// Convert a crossterm event into a TuiWidgetEvent using the default key map.
+pub fn from_crossterm(event: &Event) -> Option<TuiWidgetEvent> {
+    TuiWidgetEvent::try_from(event).ok()
+}
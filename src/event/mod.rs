@@ -0,0 +1,123 @@
+//! Mapping of key presses to [TuiWidgetEvent]s.
+//!
+//! The key map is backend independent and works on [TuiWidgetKey].
+//! The default key map implements the key commands as documented for the smart widget.
+//!
+//! With feature `crossterm` or `termion`, the backend's events can be converted into a
+//! [TuiWidgetKey] or directly into a [TuiWidgetEvent] by `TryFrom<&Event>`, which uses the
+//! default key map. The functions `from_crossterm()` and `from_termion()` do the same,
+//! but return an `Option`.
+use std::collections::HashMap;
+
+use log::LevelFilter;
+
+use crate::TuiWidgetEvent;
+
+#[cfg(feature = "crossterm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "crossterm")))]
+mod crossterm_impl;
+#[cfg(feature = "crossterm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "crossterm")))]
+pub use crossterm_impl::from_crossterm;
+#[cfg(feature = "termion")]
+#[cfg_attr(docsrs, doc(cfg(feature = "termion")))]
+mod termion_impl;
+#[cfg(feature = "termion")]
+#[cfg_attr(docsrs, doc(cfg(feature = "termion")))]
+pub use termion_impl::from_termion;
+
+#[cfg(any(feature = "crossterm", feature = "termion"))]
+lazy_static! {
+    static ref DEFAULT_KEYMAP: TuiWidgetKeyMap = TuiWidgetKeyMap::default();
+}
+
+/// Backend independent representation of a key press.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum TuiWidgetKey {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Esc,
+}
+
+/// TuiWidgetKeyMap stores the relation key->TuiWidgetEvent.
+///
+/// Usage example:
+///```
+/// use tui_logger::{TuiWidgetEvent, TuiWidgetKey, TuiWidgetKeyMap};
+///
+/// let keymap = TuiWidgetKeyMap::default()
+///     .bind(TuiWidgetKey::Char('H'), TuiWidgetEvent::HideKey)
+///     .unbind(TuiWidgetKey::Char('h'));
+/// assert_eq!(Some(TuiWidgetEvent::HideKey), keymap.get(TuiWidgetKey::Char('H')));
+/// assert_eq!(None, keymap.get(TuiWidgetKey::Char('h')));
+///```
+#[derive(Debug, Clone)]
+pub struct TuiWidgetKeyMap {
+    map: HashMap<TuiWidgetKey, TuiWidgetEvent>,
+}
+impl Default for TuiWidgetKeyMap {
+    fn default() -> Self {
+        use TuiWidgetEvent::*;
+        use TuiWidgetKey::*;
+        TuiWidgetKeyMap::empty()
+            .bind(Char('h'), HideKey)
+            .bind(Char('f'), FocusKey)
+            .bind(Up, UpKey)
+            .bind(Down, DownKey)
+            .bind(Left, LeftKey)
+            .bind(Right, RightKey)
+            .bind(Char('<'), AllLeftKey)
+            .bind(Char('>'), AllRightKey)
+            .bind(Char('1'), SetDisplayLevel(LevelFilter::Error))
+            .bind(Char('2'), SetDisplayLevel(LevelFilter::Warn))
+            .bind(Char('3'), SetDisplayLevel(LevelFilter::Info))
+            .bind(Char('4'), SetDisplayLevel(LevelFilter::Debug))
+            .bind(Char('5'), SetDisplayLevel(LevelFilter::Trace))
+            .bind(Char('-'), MinusKey)
+            .bind(Char('+'), PlusKey)
+            .bind(Char('r'), ResetKey)
+            .bind(PageUp, PrevPageKey)
+            .bind(PageDown, NextPageKey)
+            .bind(Esc, EscapeKey)
+            .bind(Home, TopKey)
+            .bind(End, BottomKey)
+            .bind(Char('p'), PauseKey)
+            .bind(Char('c'), ClearKey)
+            .bind(Char('w'), ToggleWrapKey)
+            .bind(Char('x'), ExportKey)
+            .bind(Char(' '), SpaceKey)
+    }
+}
+impl TuiWidgetKeyMap {
+    /// Create a key map with the default key commands.
+    pub fn new() -> TuiWidgetKeyMap {
+        TuiWidgetKeyMap::default()
+    }
+    /// Create a key map without any key.
+    pub fn empty() -> TuiWidgetKeyMap {
+        TuiWidgetKeyMap {
+            map: HashMap::new(),
+        }
+    }
+    /// Map the key to the event. An existing mapping of the key is replaced.
+    pub fn bind(mut self, key: TuiWidgetKey, event: TuiWidgetEvent) -> TuiWidgetKeyMap {
+        self.map.insert(key, event);
+        self
+    }
+    /// Remove the mapping of the key.
+    pub fn unbind(mut self, key: TuiWidgetKey) -> TuiWidgetKeyMap {
+        self.map.remove(&key);
+        self
+    }
+    /// Get the event mapped to the key.
+    pub fn get(&self, key: TuiWidgetKey) -> Option<TuiWidgetEvent> {
+        self.map.get(&key).copied()
+    }
+}
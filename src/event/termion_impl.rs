@@ -0,0 +1,62 @@
+//! Conversion of `termion` events into [TuiWidgetKey]s and [TuiWidgetEvent]s.
+//!
+//! The conversion into a [TuiWidgetEvent] uses the default key map.
+//! Usage example:
+//!```ignore
+//! for event in io::stdin().events() {
+//!     if let Some(widget_event) = tui_logger::event::from_termion(&event?) {
+//!         state.transition(widget_event);
+//!     }
+//! }
+//!```
+use ratatui::termion::event::{Event, Key};
+
+use super::DEFAULT_KEYMAP;
+use crate::{TuiWidgetEvent, TuiWidgetKey};
+
+impl TryFrom<&Key> for TuiWidgetKey {
+    type Error = ();
+
+    /// Only keys without modifiers are converted.
+    fn try_from(key: &Key) -> Result<Self, Self::Error> {
+        match key {
+            Key::Char(ch) => Ok(TuiWidgetKey::Char(*ch)),
+            Key::Up => Ok(TuiWidgetKey::Up),
+            Key::Down => Ok(TuiWidgetKey::Down),
+            Key::Left => Ok(TuiWidgetKey::Left),
+            Key::Right => Ok(TuiWidgetKey::Right),
+            Key::PageUp => Ok(TuiWidgetKey::PageUp),
+            Key::PageDown => Ok(TuiWidgetKey::PageDown),
+            Key::Home => Ok(TuiWidgetKey::Home),
+            Key::End => Ok(TuiWidgetKey::End),
+            Key::Esc => Ok(TuiWidgetKey::Esc),
+            _ => Err(()),
+        }
+    }
+}
+
+impl TryFrom<&Event> for TuiWidgetKey {
+    type Error = ();
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        match event {
+            Event::Key(key) => TuiWidgetKey::try_from(key),
+            _ => Err(()),
+        }
+    }
+}
+
+impl TryFrom<&Event> for TuiWidgetEvent {
+    type Error = ();
+
+    /// Convert the event using the default key map.
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        let key = TuiWidgetKey::try_from(event)?;
+        DEFAULT_KEYMAP.get(key).ok_or(())
+    }
+}
+
+/// Convert a termion event into a TuiWidgetEvent using the default key map.
+pub fn from_termion(event: &Event) -> Option<TuiWidgetEvent> {
+    TuiWidgetEvent::try_from(event).ok()
+}
@@ -0,0 +1,219 @@
+//! Export of the events in the buffer, e.g. for attaching to bug reports.
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::sync::Arc;
+
+use crate::store::{merged_iter, merged_rev_iter};
+use crate::widget::inner::TuiWidgetInnerState;
+use crate::{ansi, timezone, ExtLogRecord, LogSink, TuiLoggerFile, TuiWidgetState, TUI_LOGGER};
+
+const TIMESTAMP_FMT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+/// Clone the events of the buffer, which are displayed with the state's filters.
+/// The buffer is the one of the domain of the widget rendered with the state.
+/// Without state all events of the main buffer and the domains are returned.
+/// The state has to be locked before the logger, like for rendering.
+fn buffered_events(state: Option<&TuiWidgetInnerState>) -> Vec<ExtLogRecord> {
+    let mut tli = TUI_LOGGER.inner.lock();
+    match state {
+        Some(state) => tli
+            .domain_events(state.domain.as_deref())
+            .iter()
+            .filter(|evt| state.is_displayed(evt))
+            .cloned()
+            .collect(),
+        None => merged_iter(tli.all_stores()).cloned().collect(),
+    }
+}
+
+/// Clone the events in the buffer, for which the filter returns true, from oldest to newest,
+/// e.g. for own views, analytics or exporters. The events of the log domains are included.
+pub fn events_snapshot(filter: impl Fn(&ExtLogRecord) -> bool) -> Vec<Arc<ExtLogRecord>> {
+    let mut tli = TUI_LOGGER.inner.lock();
+    merged_iter(tli.all_stores())
+        .filter(|evt| filter(evt))
+        .map(|evt| Arc::new(evt.clone()))
+        .collect()
+}
+
+/// Clone the events in the buffer with a sequence number of at least `seq`, from oldest
+/// to newest. Returns these events together with the sequence number to pass in the next call,
+/// so a custom view can poll only the new events each frame, starting with 0.
+/// The events of the log domains are included.
+pub fn events_since(seq: u64) -> (Vec<Arc<ExtLogRecord>>, u64) {
+    let mut tli = TUI_LOGGER.inner.lock();
+    let next_seq = tli.next_seq;
+    let mut events: Vec<Arc<ExtLogRecord>> = merged_rev_iter(tli.all_stores())
+        .take_while(|evt| evt.seq >= seq)
+        .map(|evt| Arc::new(evt.clone()))
+        .collect();
+    events.reverse();
+    (events, next_seq)
+}
+
+/// Call the closure with an iterator over the events in the buffer from oldest to newest,
+/// without cloning them. The events of the log domains are included.
+/// The logger is locked meanwhile, so the closure should be short
+/// and must not call functions of tui-logger.
+pub fn with_events<R>(f: impl FnOnce(&mut dyn Iterator<Item = &ExtLogRecord>) -> R) -> R {
+    let mut tli = TUI_LOGGER.inner.lock();
+    let mut events = merged_iter(tli.all_stores());
+    f(&mut events)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Write the events in the buffer into a standalone HTML file with colored levels.
+/// With a widget state, only the events visible by its filter are exported.
+pub fn export_html(path: &str, filter: Option<&TuiWidgetState>) -> io::Result<()> {
+    let events = match filter {
+        Some(state) => buffered_events(Some(&state.inner.lock())),
+        None => buffered_events(None),
+    };
+    let timezone = timezone();
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(
+        out,
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>tui-logger export</title>
+<style>
+body {{ background: #1e1e1e; color: #d4d4d4; font-family: monospace; }}
+table {{ border-collapse: collapse; }}
+td {{ padding: 0 0.5em; vertical-align: top; white-space: pre-wrap; }}
+.ERROR {{ color: #f14c4c; }}
+.WARN {{ color: #e5e510; }}
+.INFO {{ color: #29b8db; }}
+.DEBUG {{ color: #23d18b; }}
+.TRACE {{ color: #d670d6; }}
+</style>
+</head>
+<body>
+<table>"#
+    )?;
+    for evt in events.iter() {
+        let msg = match evt.span_summary() {
+            Some(summary) => summary,
+            None => ansi::strip_ansi(&evt.msg),
+        };
+        writeln!(
+            out,
+            r#"<tr class="{}"><td>{}</td><td>{}</td><td>{}</td><td>{}:{}</td><td>{}</td></tr>"#,
+            evt.level,
+            timezone.format(&evt.timestamp, TIMESTAMP_FMT),
+            evt.level,
+            escape_html(&evt.target),
+            escape_html(&evt.file),
+            evt.line,
+            escape_html(&msg)
+        )?;
+    }
+    writeln!(out, "</table>\n</body>\n</html>")?;
+    out.flush()
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Write the events in the buffer as CSV with the columns
+/// timestamp, level, target, file, line and message, e.g. for spreadsheets.
+/// With a widget state, only the events visible by its filter are exported.
+pub fn export_csv(path: &str, filter: Option<&TuiWidgetState>) -> io::Result<()> {
+    match filter {
+        Some(state) => write_csv(path, Some(&state.inner.lock())),
+        None => write_csv(path, None),
+    }
+}
+
+/// Used by export_csv() and for TuiWidgetEvent::ExportKey with the already locked state
+pub(crate) fn write_csv(path: &str, state: Option<&TuiWidgetInnerState>) -> io::Result<()> {
+    let events = buffered_events(state);
+    let timezone = timezone();
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "timestamp,level,target,file,line,message")?;
+    for evt in events.iter() {
+        let msg = match evt.span_summary() {
+            Some(summary) => summary,
+            None => ansi::strip_ansi(&evt.msg),
+        };
+        writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            timezone.format(&evt.timestamp, TIMESTAMP_FMT),
+            evt.level,
+            escape_csv(&evt.target),
+            escape_csv(&evt.file),
+            evt.line,
+            escape_csv(&msg)
+        )?;
+    }
+    out.flush()
+}
+
+/// Write all events currently in the buffer to the file in the default format of
+/// [`TuiLoggerFile`], e.g. to capture the history after noticing a problem.
+/// An existing file is overwritten.
+pub fn dump_buffer_to_file(path: &str) -> io::Result<()> {
+    dump_buffer(TuiLoggerFile::with_writer(File::create(path)?))
+}
+
+/// Write all events currently in the buffer with the output options of the given file.
+pub fn dump_buffer(file_options: TuiLoggerFile) -> io::Result<()> {
+    dump_events(file_options, None)
+}
+
+/// Used by dump_buffer() and TuiWidgetState::export_visible()
+pub(crate) fn dump_events(
+    mut file_options: TuiLoggerFile,
+    state: Option<&TuiWidgetInnerState>,
+) -> io::Result<()> {
+    for evt in buffered_events(state).iter() {
+        file_options.write(evt);
+    }
+    file_options.dump.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html("plain text"), "plain text");
+        assert_eq!(
+            escape_html(r#"<a href="x">&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&amp;'&lt;/a&gt;"
+        );
+        // An already escaped entity is escaped again
+        assert_eq!(escape_html("&amp;"), "&amp;amp;");
+    }
+
+    #[test]
+    fn test_escape_csv() {
+        assert_eq!(escape_csv("plain text"), "plain text");
+        assert_eq!(escape_csv(""), "");
+        assert_eq!(escape_csv("a,b"), r#""a,b""#);
+        assert_eq!(escape_csv(r#"say "hi""#), r#""say ""hi""""#);
+        assert_eq!(escape_csv("line\nbreak"), "\"line\nbreak\"");
+        assert_eq!(escape_csv("cr\r"), "\"cr\r\"");
+    }
+}
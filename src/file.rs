@@ -1,38 +1,300 @@
-use std::fs::{File, OpenOptions};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use crate::TuiLoggerLevelOutput;
+use ratatui::style::Style;
 
-/// This closely follows the options of [``TuiLoggerSmartWidget``] but is used of logging to a file.
+use crate::ansi::to_sgr;
+use crate::net::record_to_json;
+use crate::timestamp::{self, Timestamp};
+use crate::{
+    format_elapsed, format_pid, start_time, timezone, ExtLogRecord, LogSink, TuiLoggerLevelOutput,
+    TuiLoggerTimezone, TUI_LOGGER,
+};
+
+/// Handling of write errors of a [`TuiLoggerFile`], e.g. on a full disk.
+/// The journald and syslog sinks use it for their send errors, too.
+#[derive(Default)]
+pub enum TuiLoggerFileErrorPolicy {
+    /// Ignore the error. This is the default.
+    #[default]
+    Ignore,
+    /// Log a warning with target `TuiLogger` on the first of consecutive errors
+    Warn,
+    /// Log a warning and stop writing after the given number of consecutive errors
+    Disable(usize),
+    /// Call the function for every error
+    Callback(Box<dyn FnMut(&io::Error) + Send>),
+}
+
+impl TuiLoggerFileErrorPolicy {
+    /// True, if the output is disabled after the given number of consecutive errors
+    pub(crate) fn disables(&self, failures: usize) -> bool {
+        matches!(self, TuiLoggerFileErrorPolicy::Disable(max) if failures >= *max)
+    }
+    /// Handle the error, which is the given number of consecutive errors writing to `output`
+    pub(crate) fn handle(&mut self, failures: usize, output: &str, err: &io::Error) {
+        let warning = match self {
+            TuiLoggerFileErrorPolicy::Ignore => None,
+            TuiLoggerFileErrorPolicy::Warn if failures == 1 => {
+                Some(format!("Writing to {} failed: {}", output, err))
+            }
+            TuiLoggerFileErrorPolicy::Warn => None,
+            TuiLoggerFileErrorPolicy::Disable(max) if failures == *max => Some(format!(
+                "Writing to {} disabled after {} errors: {}",
+                output, max, err
+            )),
+            TuiLoggerFileErrorPolicy::Disable(_) => None,
+            TuiLoggerFileErrorPolicy::Callback(callback) => {
+                callback(err);
+                None
+            }
+        };
+        if let Some(msg) = warning {
+            let now = TUI_LOGGER.now();
+            crate::push_record(ExtLogRecord::new(now, log::Level::Warn, "TuiLogger", &msg));
+        }
+    }
+}
+
+/// When a [`TuiLoggerFile`] flushes its writer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TuiLoggerFileFlush {
+    /// Only on flush_sinks(). This is the default.
+    #[default]
+    Never,
+    /// After every event
+    Record,
+    /// After every batch of events moved from the hot buffer
+    Batch,
+    /// After a batch, if the given time has passed since the last flush
+    Interval(Duration),
+}
+
+/// This closely follows the options of [`TuiLoggerSmartWidget`](crate::TuiLoggerSmartWidget)
+/// but is used for logging to a file.
+///
+/// The filename may contain a date pattern like `app-%Y-%m-%d.log`.
+/// Then the file is rotated by the timestamps of the events. Only newer timestamps switch
+/// to the next file, so an event arriving late is written to the current file.
+///
+/// An existing file is appended to, unless it is opened by [`TuiLoggerFile::create`].
+/// Instead of a named file, any writer can be used by [`TuiLoggerFile::with_writer`].
 pub struct TuiLoggerFile {
-    pub dump: File,
+    pub dump: Box<dyn Write + Send>,
+    /// Filename, possibly with date pattern. Empty for a custom writer.
+    pub fname: String,
+    /// Name of the currently opened file
+    pub current_fname: String,
+    /// Time, for which the current file has been opened. Older events do not rotate the file.
+    opened_at: Timestamp,
+    /// Truncate existing files on opening instead of appending
+    pub truncate: bool,
+    /// Write a header line with start time and pid before the first event
+    pub format_session_header: bool,
+    header_written: bool,
     pub format_separator: char,
     pub timestamp_fmt: Option<String>,
     pub format_output_target: bool,
     pub format_output_file: bool,
     pub format_output_line: bool,
     pub format_output_level: Option<TuiLoggerLevelOutput>,
+    pub relative_timestamp: bool,
+    pub timezone: Option<TuiLoggerTimezone>,
+    pub format_output_pid: bool,
+    pub format_output_json: bool,
+    /// Color the lines with ANSI escape sequences by the level styles
+    pub format_output_colors: bool,
+    pub style_error: Option<Style>,
+    pub style_warn: Option<Style>,
+    pub style_info: Option<Style>,
+    pub style_debug: Option<Style>,
+    pub style_trace: Option<Style>,
+    pub error_policy: TuiLoggerFileErrorPolicy,
+    pub flush_policy: TuiLoggerFileFlush,
+    /// Sync the file to disk on flush
+    pub sync: bool,
+    /// Handle of the opened file for syncing. None for a custom writer.
+    sync_file: Option<File>,
+    last_flush: Instant,
+    /// Number of consecutive write errors
+    pub failures: usize,
+    /// Number of rotated files to keep. None keeps all.
+    pub keep_rotated: Option<usize>,
+    /// Compress the rotated files with gzip
+    #[cfg(feature = "gzip")]
+    pub compress_rotated: bool,
 }
 
 impl TuiLoggerFile {
     pub fn new(fname: &str) -> Self {
+        let opened_at = TUI_LOGGER.now();
+        let current_fname = TuiLoggerTimezone::Local.format(&opened_at, fname);
+        let dump = open(&current_fname, false).expect("Failed to open dump File");
         TuiLoggerFile {
-            dump: OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(fname)
-                .expect("Failed to open dump File"),
+            fname: fname.to_string(),
+            current_fname,
+            opened_at,
+            sync_file: dump.try_clone().ok(),
+            ..Self::with_writer(dump)
+        }
+    }
+    /// Like [`TuiLoggerFile::new`], but an existing file is truncated,
+    /// so each run starts with a clean file.
+    pub fn create(fname: &str) -> Self {
+        Self::new(fname).truncate(true)
+    }
+    /// Dump to the given writer, e.g. a pipe, a socket or an in-memory buffer.
+    /// Rotation is not applicable then.
+    pub fn with_writer(writer: impl Write + Send + 'static) -> Self {
+        TuiLoggerFile {
+            dump: Box::new(writer),
+            fname: String::new(),
+            current_fname: String::new(),
+            opened_at: TUI_LOGGER.now(),
+            truncate: false,
+            format_session_header: false,
+            header_written: false,
             format_separator: ':',
             timestamp_fmt: Some("[%Y:%m:%d %H:%M:%S]".to_string()),
             format_output_file: true,
             format_output_line: true,
             format_output_target: true,
             format_output_level: Some(TuiLoggerLevelOutput::Long),
+            relative_timestamp: false,
+            timezone: None,
+            format_output_pid: false,
+            format_output_json: false,
+            format_output_colors: false,
+            style_error: None,
+            style_warn: None,
+            style_info: None,
+            style_debug: None,
+            style_trace: None,
+            error_policy: TuiLoggerFileErrorPolicy::Ignore,
+            failures: 0,
+            flush_policy: TuiLoggerFileFlush::Never,
+            sync: false,
+            sync_file: None,
+            last_flush: Instant::now(),
+            keep_rotated: None,
+            #[cfg(feature = "gzip")]
+            compress_rotated: false,
+        }
+    }
+    /// Truncate existing files instead of appending to them.
+    /// The currently opened file is truncated immediately.
+    pub fn truncate(mut self, enabled: bool) -> Self {
+        self.truncate = enabled;
+        if enabled && !self.current_fname.is_empty() {
+            if let Ok(dump) = open(&self.current_fname, true) {
+                self.sync_file = dump.try_clone().ok();
+                self.dump = Box::new(dump);
+            }
+        }
+        self
+    }
+    /// Write a header line with the start time and the pid before the first event,
+    /// to separate the runs in an appended file. Not written in JSON format.
+    pub fn session_header(mut self, enabled: bool) -> Self {
+        self.format_session_header = enabled;
+        self
+    }
+    /// Start a new file every day by inserting the date into the filename,
+    /// e.g. `app.log` becomes `app-%Y-%m-%d.log`.
+    pub fn rotate_daily(mut self) -> Self {
+        if !self.fname.is_empty() && !self.fname.contains('%') {
+            let fname = match self.fname.rsplit_once('.') {
+                Some((stem, ext)) if !stem.is_empty() && !ext.contains('/') => {
+                    format!("{}-%Y-%m-%d.{}", stem, ext)
+                }
+                _ => format!("{}-%Y-%m-%d", self.fname),
+            };
+            self.fname = fname;
+            // The file opened by new() is not needed, if nothing has been written yet
+            if let Some(initial) = self.switch_to(&TUI_LOGGER.now()) {
+                if fs::metadata(&initial).is_ok_and(|meta| meta.len() == 0) {
+                    let _ = fs::remove_file(initial);
+                }
+            }
         }
+        self
+    }
+    /// Keep at most the given number of rotated files. The files with the oldest dates
+    /// in their names are deleted on rotation. Files not matching the pattern are kept.
+    pub fn keep_rotated(mut self, count: usize) -> Self {
+        self.keep_rotated = Some(count);
+        self
+    }
+    /// Compress the rotated files with gzip, e.g. `app-2024-01-31.log.gz`
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+    pub fn compress_rotated(mut self, enabled: bool) -> Self {
+        self.compress_rotated = enabled;
+        self
+    }
+    /// Switch to the file for the given timestamp, if the filename contains a date pattern
+    /// and the timestamp is not older than the current file. If the new file cannot be opened,
+    /// the current one is kept.
+    /// Compression and retention of the rotated files are performed in a separate thread.
+    pub(crate) fn rotate(&mut self, timestamp: &Timestamp) {
+        let Some(rotated) = self.switch_to(timestamp) else {
+            return;
+        };
+        #[cfg(feature = "gzip")]
+        let compress = self.compress_rotated;
+        #[cfg(not(feature = "gzip"))]
+        let compress = false;
+        if !compress && self.keep_rotated.is_none() {
+            return;
+        }
+        let fname = self.fname.clone();
+        let current = self.current_fname.clone();
+        let keep = self.keep_rotated;
+        let _ = thread::Builder::new()
+            .name("tui-logger::rotate".into())
+            .spawn(move || {
+                if compress {
+                    let _ = compress_file(&rotated);
+                }
+                if let Some(keep) = keep {
+                    let files = rotated_files(&fname, &current);
+                    let excess = files.len().saturating_sub(keep);
+                    for path in files.into_iter().take(excess) {
+                        let _ = fs::remove_file(path);
+                    }
+                }
+            });
+    }
+    /// Returns the name of the previous file, if a new file has been opened
+    fn switch_to(&mut self, timestamp: &Timestamp) -> Option<String> {
+        if !self.fname.contains('%') || *timestamp < self.opened_at {
+            return None;
+        }
+        let fname = TuiLoggerTimezone::Local.format(timestamp, &self.fname);
+        if fname == self.current_fname {
+            return None;
+        }
+        let dump = open(&fname, self.truncate).ok()?;
+        // Pending output belongs to the previous file
+        self.flush_dump();
+        self.sync_file = dump.try_clone().ok();
+        self.dump = Box::new(dump);
+        self.opened_at = *timestamp;
+        Some(std::mem::replace(&mut self.current_fname, fname))
     }
     pub fn output_target(mut self, enabled: bool) -> Self {
         self.format_output_target = enabled;
         self
     }
+    /// Output the id of the process, which has logged the event.
+    /// Useful, if several processes append to the same file.
+    pub fn output_pid(mut self, enabled: bool) -> Self {
+        self.format_output_pid = enabled;
+        self
+    }
     pub fn output_file(mut self, enabled: bool) -> Self {
         self.format_output_file = enabled;
         self
@@ -45,6 +307,17 @@ impl TuiLoggerFile {
         self.timestamp_fmt = fmt;
         self
     }
+    /// Output the timestamp as time elapsed since init_logger(), e.g. 00:03:12.456.
+    /// The timestamp's format string is not used then.
+    pub fn output_relative_timestamp(mut self, enabled: bool) -> Self {
+        self.relative_timestamp = enabled;
+        self
+    }
+    /// Output the timestamp in the given timezone instead of the one set by set_timezone()
+    pub fn output_timezone(mut self, timezone: TuiLoggerTimezone) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
     pub fn output_separator(mut self, sep: char) -> Self {
         self.format_separator = sep;
         self
@@ -53,4 +326,440 @@ impl TuiLoggerFile {
         self.format_output_level = level;
         self
     }
+    /// Color the lines with ANSI escape sequences, e.g. for output to a pty or `less -R`.
+    /// Use the same level styles as for the widget to get the same look.
+    pub fn output_colors(mut self, enabled: bool) -> Self {
+        self.format_output_colors = enabled;
+        self
+    }
+    pub fn style_error(mut self, style: Style) -> Self {
+        self.style_error = Some(style);
+        self
+    }
+    pub fn style_warn(mut self, style: Style) -> Self {
+        self.style_warn = Some(style);
+        self
+    }
+    pub fn style_info(mut self, style: Style) -> Self {
+        self.style_info = Some(style);
+        self
+    }
+    pub fn style_debug(mut self, style: Style) -> Self {
+        self.style_debug = Some(style);
+        self
+    }
+    pub fn style_trace(mut self, style: Style) -> Self {
+        self.style_trace = Some(style);
+        self
+    }
+    /// Define the handling of write errors
+    pub fn on_error(mut self, policy: TuiLoggerFileErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
+    /// Define when the output is flushed. Independent of this, flush_sinks() flushes it.
+    pub fn flush_on(mut self, policy: TuiLoggerFileFlush) -> Self {
+        self.flush_policy = policy;
+        self
+    }
+    /// Sync the file to disk on each flush, so the events survive a crash of the system
+    pub fn sync(mut self, enabled: bool) -> Self {
+        self.sync = enabled;
+        self
+    }
+    fn disabled(&self) -> bool {
+        self.error_policy.disables(self.failures)
+    }
+    fn flush_dump(&mut self) {
+        if self.disabled() {
+            return;
+        }
+        self.last_flush = Instant::now();
+        let mut result = self.dump.flush();
+        if self.sync {
+            if let (Ok(()), Some(file)) = (&result, self.sync_file.as_ref()) {
+                result = file.sync_data();
+            }
+        }
+        if let Err(err) = result {
+            self.handle_error(err);
+        }
+    }
+    fn handle_error(&mut self, err: io::Error) {
+        self.failures += 1;
+        let output = format!("log file {}", self.current_fname);
+        self.error_policy.handle(self.failures, &output, &err);
+    }
+    /// Write each event as one JSON object per line, e.g. for jq, Loki or vector.
+    /// The object contains seq, timestamp, level, target, file, line, msg,
+    /// pid (if known) and fields (if any). The other output options are not used then.
+    pub fn format_json(mut self, enabled: bool) -> Self {
+        self.format_output_json = enabled;
+        self
+    }
+    fn format_line(
+        &self,
+        log_entry: &ExtLogRecord,
+        start_time: Timestamp,
+        default_timezone: TuiLoggerTimezone,
+    ) -> String {
+        let mut output = String::new();
+        let (lev_long, lev_abbr, with_loc) = match log_entry.level {
+            log::Level::Error => ("ERROR", "E", true),
+            log::Level::Warn => ("WARN ", "W", true),
+            log::Level::Info => ("INFO ", "I", false),
+            log::Level::Debug => ("DEBUG", "D", true),
+            log::Level::Trace => ("TRACE", "T", true),
+        };
+        if let Some(fmt) = self.timestamp_fmt.as_ref() {
+            if self.relative_timestamp {
+                output.push_str(&format_elapsed(log_entry.timestamp.delta(&start_time)));
+            } else {
+                let timezone = self.timezone.unwrap_or(default_timezone);
+                output.push_str(&timezone.format(&log_entry.timestamp, fmt));
+            }
+            output.push(self.format_separator);
+        }
+        match self.format_output_level {
+            None => {}
+            Some(TuiLoggerLevelOutput::Abbreviated) => {
+                output.push_str(lev_abbr);
+                output.push(self.format_separator);
+            }
+            Some(TuiLoggerLevelOutput::Long) => {
+                output.push_str(lev_long);
+                output.push(self.format_separator);
+            }
+        }
+        if self.format_output_pid {
+            output.push_str(&format_pid(log_entry.pid));
+            output.push(self.format_separator);
+        }
+        if self.format_output_target {
+            output.push_str(&log_entry.target);
+            output.push(self.format_separator);
+        }
+        if with_loc {
+            if self.format_output_file {
+                output.push_str(&log_entry.file);
+                output.push(self.format_separator);
+            }
+            if self.format_output_line {
+                output.push_str(&format!("{}", log_entry.line));
+                output.push(self.format_separator);
+            }
+        }
+        match log_entry.span_summary() {
+            Some(summary) => output.push_str(&summary),
+            None => output.push_str(&log_entry.msg),
+        }
+        if let Some(backtrace) = log_entry.backtrace.as_ref() {
+            output.push('\n');
+            output.push_str(backtrace.trim_end());
+        }
+        if self.format_output_colors {
+            let style = match log_entry.level {
+                log::Level::Error => self.style_error,
+                log::Level::Warn => self.style_warn,
+                log::Level::Info => self.style_info,
+                log::Level::Debug => self.style_debug,
+                log::Level::Trace => self.style_trace,
+            };
+            let sgr = style.map(to_sgr).unwrap_or_default();
+            if !sgr.is_empty() {
+                output = format!("{}{}\x1b[0m", sgr, output);
+            }
+        }
+        output
+    }
+}
+
+impl LogSink for TuiLoggerFile {
+    fn write(&mut self, log_entry: &ExtLogRecord) {
+        if self.disabled() {
+            return;
+        }
+        self.rotate(&log_entry.timestamp);
+        let output = if self.format_output_json {
+            record_to_json(log_entry)
+        } else {
+            self.format_line(log_entry, start_time(), timezone())
+        };
+        if self.format_session_header && !self.header_written && !self.format_output_json {
+            let timezone = self.timezone.unwrap_or_else(timezone);
+            let header = format!(
+                "===== Session started {} by pid {} =====",
+                timezone.format(&start_time(), "%Y-%m-%d %H:%M:%S"),
+                std::process::id()
+            );
+            if let Err(err) = writeln!(self.dump, "{}", header) {
+                self.handle_error(err);
+                return;
+            }
+        }
+        self.header_written = true;
+        match writeln!(self.dump, "{}", output) {
+            Ok(()) => self.failures = 0,
+            Err(err) => self.handle_error(err),
+        }
+        if self.flush_policy == TuiLoggerFileFlush::Record {
+            self.flush_dump();
+        }
+    }
+    fn end_batch(&mut self) {
+        match self.flush_policy {
+            TuiLoggerFileFlush::Batch => self.flush_dump(),
+            TuiLoggerFileFlush::Interval(interval) if self.last_flush.elapsed() >= interval => {
+                self.flush_dump()
+            }
+            _ => {}
+        }
+    }
+    fn flush(&mut self) {
+        self.flush_dump();
+    }
+}
+
+fn open(fname: &str, truncate: bool) -> std::io::Result<File> {
+    if truncate {
+        OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(fname)
+    } else {
+        OpenOptions::new().create(true).append(true).open(fname)
+    }
+}
+
+/// Files in the directory of the pattern, of which the name with or without `.gz` matches
+/// the date pattern, except the current file. Sorted from the oldest to the newest date.
+fn rotated_files(pattern: &str, current: &str) -> Vec<PathBuf> {
+    let pattern = Path::new(pattern);
+    let dir = match pattern.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let Some(name) = pattern.file_name().and_then(|name| name.to_str()) else {
+        return vec![];
+    };
+    let current = Path::new(current).file_name();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut files: Vec<(i64, PathBuf)> = entries
+        .flatten()
+        .filter(|entry| Some(entry.file_name().as_os_str()) != current)
+        .filter_map(|entry| {
+            let fname = entry.file_name();
+            let fname = fname.to_string_lossy();
+            let fname = fname.strip_suffix(".gz").unwrap_or(&fname);
+            Some((timestamp::parse_date(fname, name)?, entry.path()))
+        })
+        .collect();
+    files.sort();
+    files.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Compress the file to `<fname>.gz`. An existing compressed file is appended to,
+/// which is valid as gzip with several members.
+#[cfg(feature = "gzip")]
+fn compress_file(fname: &str) -> std::io::Result<()> {
+    use flate2::{write::GzEncoder, Compression};
+
+    let mut input = File::open(fname)?;
+    let output = open(&format!("{}.gz", fname), false)?;
+    let mut encoder = GzEncoder::new(output, Compression::default());
+    std::io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    fs::remove_file(fname)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn compress_file(_fname: &str) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tui-logger-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Writer counting its flushes
+    #[derive(Clone, Default)]
+    struct Counted {
+        written: Arc<Mutex<Vec<u8>>>,
+        flushes: Arc<AtomicUsize>,
+    }
+    impl Write for Counted {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushes.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    fn plain(file: TuiLoggerFile) -> TuiLoggerFile {
+        file.output_timestamp(None)
+            .output_level(None)
+            .output_target(false)
+    }
+
+    fn info(msg: &str) -> ExtLogRecord {
+        ExtLogRecord::new(TUI_LOGGER.now(), log::Level::Info, "app", msg)
+    }
+
+    #[test]
+    fn test_flush_policy() {
+        // Number of flushes after two events and after the end of their batch
+        let flushes = |policy: TuiLoggerFileFlush| {
+            let writer = Counted::default();
+            let mut file = plain(TuiLoggerFile::with_writer(writer.clone())).flush_on(policy);
+            file.write(&info("a"));
+            file.write(&info("b"));
+            let after_write = writer.flushes.load(Ordering::Relaxed);
+            file.end_batch();
+            let after_batch = writer.flushes.load(Ordering::Relaxed);
+            file.flush();
+            assert_eq!(writer.flushes.load(Ordering::Relaxed), after_batch + 1);
+            assert_eq!(*writer.written.lock().unwrap(), b"a\nb\n");
+            (after_write, after_batch)
+        };
+        assert_eq!(flushes(TuiLoggerFileFlush::Never), (0, 0));
+        assert_eq!(flushes(TuiLoggerFileFlush::Record), (2, 2));
+        assert_eq!(flushes(TuiLoggerFileFlush::Batch), (0, 1));
+        assert_eq!(
+            flushes(TuiLoggerFileFlush::Interval(Duration::ZERO)),
+            (0, 1)
+        );
+        assert_eq!(
+            flushes(TuiLoggerFileFlush::Interval(Duration::from_secs(3600))),
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        let dir = temp_dir("truncate");
+        let fname = dir.join("app.log");
+        let fname = fname.to_str().unwrap();
+        fs::write(fname, "old\n").unwrap();
+        // By default the file is appended to
+        let mut file = plain(TuiLoggerFile::new(fname));
+        file.write(&info("appended"));
+        drop(file);
+        assert_eq!(fs::read_to_string(fname).unwrap(), "old\nappended\n");
+
+        // The opened file is truncated immediately
+        let file = plain(TuiLoggerFile::new(fname)).truncate(true);
+        assert_eq!(fs::read_to_string(fname).unwrap(), "");
+        drop(file);
+        fs::write(fname, "old\n").unwrap();
+        let mut file = plain(TuiLoggerFile::create(fname));
+        file.write(&info("new"));
+        drop(file);
+        assert_eq!(fs::read_to_string(fname).unwrap(), "new\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotated_files() {
+        let dir = temp_dir("rotated");
+        for fname in [
+            "app-2024-01-03.log",
+            "app-2024-01-01.log.gz",
+            "app-2024-01-02.log",
+            "app-2024-01-04.log",
+            "app-backup.log",
+            "app-2024-01-05.log.1",
+            "other-2024-01-01.log",
+        ] {
+            File::create(dir.join(fname)).unwrap();
+        }
+        let pattern = dir.join("app-%Y-%m-%d.log");
+        let current = dir.join("app-2024-01-04.log");
+        let files = rotated_files(pattern.to_str().unwrap(), current.to_str().unwrap());
+        let names: Vec<_> = files
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "app-2024-01-01.log.gz",
+                "app-2024-01-02.log",
+                "app-2024-01-03.log"
+            ]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotate_forward_only() {
+        let dir = temp_dir("rotate");
+        let pattern = dir.join("app-%Y-%m-%d.log");
+        let mut file = TuiLoggerFile::create(pattern.to_str().unwrap())
+            .output_timestamp(None)
+            .output_level(None)
+            .output_target(false);
+        let now = TUI_LOGGER.now();
+        let mut write = |days: i64, msg: &str| {
+            let offset = Duration::from_secs(days.unsigned_abs() * 86_400);
+            let timestamp = if days < 0 { now - offset } else { now + offset };
+            file.write(&ExtLogRecord::new(timestamp, log::Level::Info, "app", msg));
+            file.current_fname.clone()
+        };
+        let today = write(0, "today");
+        let tomorrow = write(1, "tomorrow");
+        assert_ne!(today, tomorrow);
+        let next = write(2, "next day");
+        // A late event neither switches back nor truncates the previous file
+        assert_eq!(write(1, "late"), next);
+        assert_eq!(write(-1, "very late"), next);
+        assert_eq!(fs::read_to_string(&tomorrow).unwrap(), "tomorrow\n");
+        assert_eq!(
+            fs::read_to_string(&next).unwrap(),
+            "next day\nlate\nvery late\n"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_compress_file() {
+        use flate2::read::MultiGzDecoder;
+        use std::io::Read;
+
+        let dir = temp_dir("compress");
+        let fname = dir.join("app-2024-01-01.log");
+        let fname = fname.to_str().unwrap();
+        fs::write(fname, "first\n").unwrap();
+        compress_file(fname).unwrap();
+        assert!(!Path::new(fname).exists());
+        let mut content = String::new();
+        MultiGzDecoder::new(File::open(format!("{}.gz", fname)).unwrap())
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "first\n");
+        // A file with the same name is added to the compressed file
+        fs::write(fname, "second\n").unwrap();
+        compress_file(fname).unwrap();
+        let mut content = String::new();
+        MultiGzDecoder::new(File::open(format!("{}.gz", fname)).unwrap())
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "first\nsecond\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -0,0 +1,285 @@
+//! Ingestion of log lines from other processes, e.g. `some_process | my-tui-viewer`.
+use std::io::{self, BufRead, BufReader, Read};
+use std::thread;
+
+use log::Level;
+
+use crate::timestamp;
+use crate::{ExtLogRecord, TUI_LOGGER};
+
+/// Target of records created by [`PlainLineParser::default()`]
+pub const STDIN_TARGET: &str = "stdin";
+
+/// Converts lines of ingested input into records.
+///
+/// It is implemented for closures, too.
+pub trait LineParser: Send {
+    /// Returns None, if the line should be skipped
+    fn parse(&mut self, line: &str) -> Option<ExtLogRecord>;
+}
+
+impl<F> LineParser for F
+where
+    F: FnMut(&str) -> Option<ExtLogRecord> + Send,
+{
+    fn parse(&mut self, line: &str) -> Option<ExtLogRecord> {
+        self(line)
+    }
+}
+
+/// Takes every non-empty line as message with fixed level and target.
+/// The timestamp is the time of reception.
+pub struct PlainLineParser {
+    pub level: Level,
+    pub target: String,
+}
+
+impl Default for PlainLineParser {
+    fn default() -> Self {
+        PlainLineParser {
+            level: Level::Info,
+            target: STDIN_TARGET.to_string(),
+        }
+    }
+}
+
+impl LineParser for PlainLineParser {
+    fn parse(&mut self, line: &str) -> Option<ExtLogRecord> {
+        if line.trim().is_empty() {
+            return None;
+        }
+        let now = TUI_LOGGER.now();
+        Some(ExtLogRecord::new(now, self.level, &self.target, line))
+    }
+}
+
+/// Parses logfmt lines, e.g. `time=2024-01-31T12:00:00Z level=warn msg="disk full" disk=sda`.
+///
+/// The fields `level`/`lvl`, `msg`/`message`, `target`/`logger`/`module`,
+/// `time`/`ts`/`timestamp` (RFC 3339), `file` and `line` are mapped to the record.
+/// The other fields are stored as key-value pairs.
+/// Lines without a `msg` or `message` field, like `retry attempt=3 failed`, are taken as
+/// message like by [`PlainLineParser`].
+#[derive(Default)]
+pub struct LogfmtParser {
+    pub fallback: PlainLineParser,
+}
+
+impl LineParser for LogfmtParser {
+    fn parse(&mut self, line: &str) -> Option<ExtLogRecord> {
+        let fields = parse_logfmt(line);
+        if !fields
+            .iter()
+            .any(|(key, _)| key == "msg" || key == "message")
+        {
+            return self.fallback.parse(line);
+        }
+        Some(record_from_fields(fields, &self.fallback))
+    }
+}
+
+/// Parses JSON lines, e.g. `{"level":"warn","target":"db","message":"disk full"}`.
+///
+/// The fields are mapped to the record like by [`LogfmtParser`].
+/// Non-string values of other fields are stored as JSON text.
+/// Lines, which are no JSON objects, are taken as message like by [`PlainLineParser`].
+#[cfg(feature = "json-lines")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json-lines")))]
+#[derive(Default)]
+pub struct JsonLinesParser {
+    pub fallback: PlainLineParser,
+}
+
+#[cfg(feature = "json-lines")]
+impl LineParser for JsonLinesParser {
+    fn parse(&mut self, line: &str) -> Option<ExtLogRecord> {
+        let Ok(serde_json::Value::Object(object)) = serde_json::from_str(line) else {
+            return self.fallback.parse(line);
+        };
+        let fields = object
+            .into_iter()
+            .map(|(key, value)| match value {
+                serde_json::Value::String(value) => (key, value),
+                value => (key, value.to_string()),
+            })
+            .collect();
+        Some(record_from_fields(fields, &self.fallback))
+    }
+}
+
+/// Split the line into key-value pairs. Keys without value get an empty value.
+fn parse_logfmt(line: &str) -> Vec<(String, String)> {
+    let mut fields = vec![];
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+        let mut key = String::new();
+        while let Some(ch) = chars.next_if(|&ch| ch != '=' && !ch.is_whitespace()) {
+            key.push(ch);
+        }
+        if key.is_empty() {
+            if chars.next().is_none() {
+                return fields;
+            }
+            // a stray '=' is skipped
+            continue;
+        }
+        let mut value = String::new();
+        if chars.next_if_eq(&'=').is_some() {
+            if chars.next_if_eq(&'"').is_some() {
+                while let Some(ch) = chars.next() {
+                    match ch {
+                        '"' => break,
+                        '\\' => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some(ch) => value.push(ch),
+                            None => break,
+                        },
+                        ch => value.push(ch),
+                    }
+                }
+            } else {
+                while let Some(ch) = chars.next_if(|ch| !ch.is_whitespace()) {
+                    value.push(ch);
+                }
+            }
+        }
+        fields.push((key, value));
+    }
+}
+
+fn parse_level(level: &str) -> Option<Level> {
+    match level.to_ascii_lowercase().as_str() {
+        "error" | "err" | "fatal" | "critical" | "crit" => Some(Level::Error),
+        "warn" | "warning" => Some(Level::Warn),
+        "info" | "information" | "notice" => Some(Level::Info),
+        "debug" | "dbg" => Some(Level::Debug),
+        "trace" => Some(Level::Trace),
+        _ => None,
+    }
+}
+
+fn record_from_fields(fields: Vec<(String, String)>, fallback: &PlainLineParser) -> ExtLogRecord {
+    let mut level = fallback.level;
+    let mut target = fallback.target.clone();
+    let mut msg = String::new();
+    let mut timestamp = None;
+    let mut file = None;
+    let mut line = 0;
+    let mut key_values = vec![];
+    for (key, value) in fields {
+        match key.as_str() {
+            "level" | "lvl" | "severity" => match parse_level(&value) {
+                Some(parsed) => level = parsed,
+                None => key_values.push((key, value)),
+            },
+            "msg" | "message" => msg = value,
+            "target" | "logger" | "module" => target = value,
+            "time" | "ts" | "timestamp" => match timestamp::parse_rfc3339(&value) {
+                Some(parsed) => timestamp = Some(parsed),
+                None => key_values.push((key, value)),
+            },
+            "file" => file = Some(value),
+            "line" => match value.parse() {
+                Ok(parsed) => line = parsed,
+                Err(_) => key_values.push((key, value)),
+            },
+            _ => key_values.push((key, value)),
+        }
+    }
+    let timestamp = timestamp.unwrap_or_else(|| TUI_LOGGER.now());
+    let mut record = ExtLogRecord::new(timestamp, level, &target, &msg);
+    if let Some(file) = file {
+        record = record.location(&file, line);
+    }
+    record.key_values = key_values;
+    record
+}
+
+/// Spawn a thread, which reads the lines from the reader and feeds the parsed records
+/// into the buffer. The log levels are not checked. The thread ends at end of input.
+pub fn ingest_reader<R, P>(reader: R, mut parser: P) -> io::Result<thread::JoinHandle<()>>
+where
+    R: Read + Send + 'static,
+    P: LineParser + 'static,
+{
+    thread::Builder::new()
+        .name("tui-logger::ingest".into())
+        .spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if let Some(record) = parser.parse(&line) {
+                    crate::push_record(record);
+                }
+            }
+        })
+}
+
+/// Spawn a thread, which reads the lines of stdin and feeds the parsed records
+/// into the buffer. Use [`PlainLineParser::default()`] to show the lines as they are.
+pub fn ingest_stdin<P>(parser: P) -> io::Result<thread::JoinHandle<()>>
+where
+    P: LineParser + 'static,
+{
+    ingest_reader(io::stdin(), parser)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_parse_logfmt() {
+        let fields = parse_logfmt(r#"level=warn msg="disk \"sda\" full" retry  ratio=0.9"#);
+        assert_eq!(
+            fields,
+            vec![
+                pair("level", "warn"),
+                pair("msg", "disk \"sda\" full"),
+                pair("retry", ""),
+                pair("ratio", "0.9"),
+            ]
+        );
+    }
+    #[test]
+    fn test_logfmt_fallback() {
+        let mut parser = LogfmtParser::default();
+        let record = parser.parse("level=warn msg=full disk=sda").unwrap();
+        assert_eq!((record.level, record.msg.as_str()), (Level::Warn, "full"));
+        // Without a message field, the whole line is the message
+        let record = parser.parse("retry attempt=3 failed").unwrap();
+        assert_eq!(record.level, Level::Info);
+        assert_eq!(record.msg, "retry attempt=3 failed");
+        assert!(record.key_values.is_empty());
+        assert!(parser.parse("  ").is_none());
+    }
+    #[test]
+    fn test_record_from_fields() {
+        let fields = vec![
+            pair("time", "2024-01-31T12:00:00Z"),
+            pair("level", "WARNING"),
+            pair("logger", "db"),
+            pair("msg", "disk full"),
+            pair("file", "src/db.rs"),
+            pair("line", "42"),
+            pair("disk", "sda"),
+        ];
+        let record = record_from_fields(fields, &PlainLineParser::default());
+        assert_eq!(record.level, Level::Warn);
+        assert_eq!(record.target, "db");
+        assert_eq!(record.msg, "disk full");
+        assert_eq!((record.file.as_str(), record.line), ("src/db.rs", 42));
+        assert_eq!(
+            record.timestamp,
+            timestamp::parse_rfc3339("2024-01-31T12:00:00Z").unwrap()
+        );
+        assert_eq!(record.key_values, vec![pair("disk", "sda")]);
+    }
+}
@@ -0,0 +1,118 @@
+//! Sink to systemd-journald using its native protocol.
+use std::io;
+use std::os::unix::net::UnixDatagram;
+
+use crate::{ExtLogRecord, LogSink, TuiLoggerFileErrorPolicy};
+
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Sends the captured events additionally to systemd-journald.
+///
+/// Each event is sent with the fields `MESSAGE`, `PRIORITY`, `CODE_FILE`, `CODE_LINE`,
+/// `TARGET`, `SYSLOG_IDENTIFIER` and its key-value pairs with uppercase field names.
+pub struct TuiLoggerJournald {
+    pub socket: UnixDatagram,
+    pub syslog_identifier: String,
+    pub error_policy: TuiLoggerFileErrorPolicy,
+    /// Number of consecutive send errors
+    pub failures: usize,
+}
+
+impl TuiLoggerJournald {
+    /// Connect to the journald socket.
+    /// The syslog identifier defaults to the name of the executable.
+    pub fn new() -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(JOURNALD_SOCKET)?;
+        let syslog_identifier = std::env::current_exe()
+            .ok()
+            .and_then(|exe| {
+                exe.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| "tui-logger".to_string());
+        Ok(TuiLoggerJournald {
+            socket,
+            syslog_identifier,
+            error_policy: TuiLoggerFileErrorPolicy::Ignore,
+            failures: 0,
+        })
+    }
+    pub fn syslog_identifier(mut self, identifier: &str) -> Self {
+        self.syslog_identifier = identifier.to_string();
+        self
+    }
+    /// Define the handling of send errors like for the file dump
+    pub fn on_error(mut self, policy: TuiLoggerFileErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
+    fn send(&self, log_entry: &ExtLogRecord) -> io::Result<()> {
+        let priority = match log_entry.level {
+            log::Level::Error => "3",
+            log::Level::Warn => "4",
+            log::Level::Info => "6",
+            log::Level::Debug | log::Level::Trace => "7",
+        };
+        let mut datagram = Vec::new();
+        add_field(&mut datagram, "MESSAGE", &log_entry.msg);
+        add_field(&mut datagram, "PRIORITY", priority);
+        add_field(&mut datagram, "CODE_FILE", &log_entry.file);
+        add_field(&mut datagram, "CODE_LINE", &log_entry.line.to_string());
+        add_field(&mut datagram, "TARGET", &log_entry.target);
+        add_field(&mut datagram, "SYSLOG_IDENTIFIER", &self.syslog_identifier);
+        for (key, value) in log_entry.key_values.iter() {
+            if let Some(name) = field_name(key) {
+                add_field(&mut datagram, &name, value);
+            }
+        }
+        self.socket.send(&datagram).map(|_| ())
+    }
+}
+
+impl LogSink for TuiLoggerJournald {
+    fn write(&mut self, log_entry: &ExtLogRecord) {
+        if self.error_policy.disables(self.failures) {
+            return;
+        }
+        match self.send(log_entry) {
+            Ok(()) => self.failures = 0,
+            Err(err) => {
+                self.failures += 1;
+                self.error_policy.handle(self.failures, "journald", &err);
+            }
+        }
+    }
+}
+
+/// Journald accepts only uppercase letters, digits and underscores,
+/// and field names must not start with an underscore or a digit.
+fn field_name(key: &str) -> Option<String> {
+    let name: String = key
+        .chars()
+        .map(|ch| match ch {
+            'a'..='z' => ch.to_ascii_uppercase(),
+            'A'..='Z' | '0'..='9' => ch,
+            _ => '_',
+        })
+        .collect();
+    let name = name.trim_start_matches(|ch: char| ch == '_' || ch.is_ascii_digit());
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+/// Values with newlines need the binary encoding with explicit length
+fn add_field(datagram: &mut Vec<u8>, name: &str, value: &str) {
+    datagram.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        datagram.push(b'\n');
+        datagram.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        datagram.push(b'=');
+    }
+    datagram.extend_from_slice(value.as_bytes());
+    datagram.push(b'\n');
+}
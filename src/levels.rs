@@ -0,0 +1,283 @@
+//! Persistence of the capture levels, so the tuning per target survives restarts.
+//!
+//! The levels are stored as TOML:
+//! ```toml
+//! default = "INFO"
+//!
+//! [targets]
+//! "app::db" = "DEBUG"
+//! ```
+use std::env;
+use std::fs;
+use std::io;
+use std::str::FromStr;
+
+use log::LevelFilter;
+
+use crate::{
+    reset_level_for_target, set_default_level, set_env_directives, set_level_for_target, TUI_LOGGER,
+};
+
+/// Write the default capture level and the capture level of all known targets to the file.
+pub fn save_levels(path: &str) -> io::Result<()> {
+    let (default, targets) = {
+        let tli = TUI_LOGGER.inner.lock();
+        let mut targets: Vec<(String, LevelFilter)> = tli
+            .targets
+            .iter()
+            .map(|(target, levelfilter)| (target.clone(), *levelfilter))
+            .collect();
+        targets.sort();
+        (tli.default, targets)
+    };
+    fs::write(path, format_levels(default, &targets))
+}
+
+/// Restore the capture levels saved by [`save_levels`].
+/// Targets, which are not contained in the file, keep their level.
+pub fn load_levels(path: &str) -> io::Result<()> {
+    let content = fs::read_to_string(path)?;
+    let (default, targets) = parse_toml(&content)?;
+    if let Some(default) = default {
+        set_default_level(default);
+    }
+    for (target, levelfilter) in targets {
+        set_level_for_target(&target, levelfilter);
+    }
+    Ok(())
+}
+
+/// Set the capture levels by a filter in the syntax of RUST_LOG,
+/// e.g. `info,my_crate=debug,hyper=warn`. A bare level sets the default level,
+/// otherwise the default level is kept. Invalid directives are skipped.
+/// Like for RUST_LOG, a directive applies to the targets below it, too,
+/// e.g. `my_crate` to `my_crate::db`, unless a more specific directive or
+/// [`set_level_for_target`] sets their level.
+///
+/// Targets, which have been set by the previous filter but are not contained in this one,
+/// follow the default level again.
+pub fn set_env_filter(filter: &str) {
+    let (default, targets) = parse_env_filter(filter);
+    if let Some(default) = default {
+        set_default_level(default);
+    }
+    let previous = set_env_directives(targets.clone());
+    for (target, _) in previous {
+        if !targets.iter().any(|(t, _)| *t == target) {
+            reset_level_for_target(&target);
+        }
+    }
+    for (target, levelfilter) in targets {
+        set_level_for_target(&target, levelfilter);
+    }
+}
+
+/// Apply the environment variable RUST_LOG by [`set_env_filter`], e.g. after it has been
+/// changed at runtime. If it is not set, the targets of the previous filter are reset.
+pub fn reload_env_filter() {
+    set_env_filter(&env::var("RUST_LOG").unwrap_or_default());
+}
+
+fn format_levels(default: LevelFilter, targets: &[(String, LevelFilter)]) -> String {
+    let mut output = format!("default = \"{}\"\n\n[targets]\n", default);
+    for (target, levelfilter) in targets {
+        output.push_str(&format!("{} = \"{}\"\n", quote(target), levelfilter));
+    }
+    output
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+pub(crate) type Levels = (Option<LevelFilter>, Vec<(String, LevelFilter)>);
+
+/// Parse the default level and the levels per target. Used for TuiWidgetState::from_toml(), too.
+pub(crate) fn parse_toml(content: &str) -> io::Result<Levels> {
+    parse_levels(content).map_err(|msg| io::Error::new(io::ErrorKind::InvalidData, msg))
+}
+
+fn parse_levels(content: &str) -> Result<Levels, String> {
+    let mut default = None;
+    let mut targets = vec![];
+    let mut in_targets = false;
+    for (nr, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_targets = line == "[targets]";
+            continue;
+        }
+        let err = |msg: &str| format!("line {}: {}", nr + 1, msg);
+        let (key, rest) = parse_key(line).ok_or_else(|| err("invalid key"))?;
+        let value = rest
+            .trim_start()
+            .strip_prefix('=')
+            .and_then(|value| parse_string(value.trim_start()))
+            .ok_or_else(|| err("expected quoted level"))?;
+        let levelfilter = LevelFilter::from_str(&value).map_err(|_| err("invalid level"))?;
+        if in_targets {
+            targets.push((key, levelfilter));
+        } else if key == "default" {
+            default = Some(levelfilter);
+        }
+    }
+    Ok((default, targets))
+}
+
+/// Parse a filter in the syntax of RUST_LOG, e.g. `info,my_crate=debug,hyper=warn`.
+/// A directive without level enables all levels of the target. Invalid directives are skipped.
+pub(crate) fn parse_env_filter(spec: &str) -> Levels {
+    let mut default = None;
+    let mut targets = vec![];
+    // Filtering of the messages by regex after '/' is not supported
+    let spec = spec.split('/').next().unwrap_or("");
+    for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+        match directive.split_once('=') {
+            Some((target, level)) => {
+                if let Ok(levelfilter) = LevelFilter::from_str(level.trim()) {
+                    targets.push((target.trim().to_string(), levelfilter));
+                }
+            }
+            None => match LevelFilter::from_str(directive) {
+                Ok(levelfilter) => default = Some(levelfilter),
+                Err(_) => targets.push((directive.to_string(), LevelFilter::Trace)),
+            },
+        }
+    }
+    (default, targets)
+}
+
+/// A directive matches the target itself and all targets below it,
+/// e.g. `net` matches `net` and `net::http`, but not `network`.
+pub(crate) fn is_below(prefix: &str, target: &str) -> bool {
+    target
+        .strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+/// The level of the most specific directive matching the target
+pub(crate) fn directive_level(
+    directives: &[(String, LevelFilter)],
+    target: &str,
+) -> Option<LevelFilter> {
+    directives
+        .iter()
+        .filter(|(prefix, _)| is_below(prefix, target))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, levelfilter)| *levelfilter)
+}
+
+/// Returns the key and the rest of the line
+fn parse_key(line: &str) -> Option<(String, &str)> {
+    if line.starts_with('"') {
+        let end = closing_quote(line)?;
+        Some((unescape(&line[1..end]), &line[end + 1..]))
+    } else {
+        let end = line.find(|ch: char| ch == '=' || ch.is_whitespace())?;
+        Some((line[..end].to_string(), &line[end..]))
+    }
+}
+
+fn parse_string(value: &str) -> Option<String> {
+    let end = closing_quote(value)?;
+    let trailing = value[end + 1..].trim();
+    if trailing.is_empty() || trailing.starts_with('#') {
+        Some(unescape(&value[1..end]))
+    } else {
+        None
+    }
+}
+
+/// Position of the quote, which closes the string starting at position 0
+fn closing_quote(value: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, ch) in value.char_indices().skip(1) {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn unescape(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            if let Some(ch) = chars.next() {
+                output.push(ch);
+            }
+        } else {
+            output.push(ch);
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_roundtrip() {
+        let targets = vec![
+            ("app::db".to_string(), LevelFilter::Debug),
+            ("odd \"name\\\"".to_string(), LevelFilter::Off),
+        ];
+        let content = format_levels(LevelFilter::Warn, &targets);
+        assert_eq!(
+            parse_levels(&content),
+            Ok((Some(LevelFilter::Warn), targets))
+        );
+    }
+
+    #[test]
+    fn test_parse_levels() {
+        let content = "# comment\ndefault = \"info\"\n[targets]\nbare = \"TRACE\" # note\n";
+        assert_eq!(
+            parse_levels(content),
+            Ok((
+                Some(LevelFilter::Info),
+                vec![("bare".to_string(), LevelFilter::Trace)]
+            ))
+        );
+        assert!(parse_levels("[targets]\nbare = \"loud\"\n").is_err());
+        assert!(parse_levels("default = INFO\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_env_filter() {
+        assert_eq!(
+            parse_env_filter("warn, my_crate=debug,hyper=loud,tokio,app=OFF/foo"),
+            (
+                Some(LevelFilter::Warn),
+                vec![
+                    ("my_crate".to_string(), LevelFilter::Debug),
+                    ("tokio".to_string(), LevelFilter::Trace),
+                    ("app".to_string(), LevelFilter::Off),
+                ]
+            )
+        );
+        assert_eq!(parse_env_filter(""), (None, vec![]));
+    }
+
+    #[test]
+    fn test_directive_level() {
+        let directives = vec![
+            ("my_crate".to_string(), LevelFilter::Debug),
+            ("my_crate::db".to_string(), LevelFilter::Warn),
+        ];
+        let level = |target| directive_level(&directives, target);
+        assert_eq!(level("my_crate"), Some(LevelFilter::Debug));
+        assert_eq!(level("my_crate::net"), Some(LevelFilter::Debug));
+        assert_eq!(level("my_crate::db::pool"), Some(LevelFilter::Warn));
+        assert_eq!(level("my_crate_ext"), None);
+        assert_eq!(level("other"), None);
+    }
+}
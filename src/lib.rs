@@ -38,7 +38,8 @@
 //! - [X] Support to use custom formatter for log events
 //! - [ ] Allow configuration of target dependent loglevel specifically for file logging
 //! - [ ] Avoid duplicating of target, module and filename in every log record
-//! - [ ] Simultaneous modification of all targets' display/hot logging loglevel by key command
+//! - [X] Simultaneous modification of all targets' display loglevel by key command
+//! - [ ] Simultaneous modification of all targets' hot logging loglevel by key command
 //!
 //! ## Smart Widget
 //!
@@ -74,21 +75,43 @@
 //! | DOWN     | Select next target in target selector widget
 //! | LEFT     | Reduce SHOWN (!) log messages by one level
 //! | RIGHT    | Increase SHOWN (!) log messages by one level
+//! | <        | Reduce SHOWN (!) log messages by one level for all targets
+//! | >        | Increase SHOWN (!) log messages by one level for all targets
+//! | 1..5     | Set SHOWN (!) log messages of selected target to Error/Warn/Info/Debug/Trace
 //! | -        | Reduce CAPTURED (!) log messages by one level
 //! | +        | Increase CAPTURED (!) log messages by one level
+//! | r        | Reset SHOWN and CAPTURED log levels of all targets to the defaults
 //! | PAGEUP   | Enter Page Mode and scroll approx. half page up in log history.
 //! | PAGEDOWN | Only in page mode: scroll 10 events down in log history.
 //! | ESCAPE   | Exit page mode and go back to scrolling mode
+//! | HOME     | Enter page mode and jump to the oldest event in log history
+//! | END      | Exit page mode and go back to scrolling mode
+//! | p        | Toggles freezing of the log view, while capturing of events continues
+//! | c        | Clear all events in the log history
+//! | w        | Toggles wrapping of long events vs. one line per event
+//! | x        | Export the displayed events as CSV, if an export path is set in the state
 //! | SPACE    | Toggles hiding of targets, which have logfilter set to off
 //! ```
 //!
 //! The mapping of key to action has to be done in the application. The respective TuiWidgetEvent
 //! has to be provided to TuiWidgetState::transition().
 //!
-//! Remark to the page mode: The timestamp of the event at event history's bottom line is used as
+//! Alternatively the application converts its key events into a [`TuiWidgetKey`] and
+//! calls TuiWidgetState::handle_key() with a [`TuiWidgetKeyMap`]. The default key map
+//! implements the key commands as listed above and can be modified by bind()/unbind().
+//!
+//! TuiWidgetState::handle_event() and handle_key() return a [`TuiWidgetEventResult`], which
+//! tells if the event has been handled and if a redraw is needed. For example ESCAPE outside
+//! of page mode is reported as `NotHandled` and can be used by the application.
+//!
+//! Without any key events, the log view can be scrolled by TuiWidgetState::scroll_lines(),
+//! scroll_pages(), scroll_to_start() and scroll_to_end().
+//!
+//! Remark to the page mode: The sequence number of the event at event history's bottom line is used as
 //! reference. This means, changing the filters in the EWIDT/focus from the target selector window
 //! should work as expected without jumps in the history. The page next/forward advances as
-//! per visibility of the events.
+//! per visibility of the events. If the referenced event is filtered out, the nearest visible
+//! event is shown at the bottom line instead. Changing the filter back returns to the referenced event.
 //!
 //! ## Basic usage to initialize logger-system:
 //! ```rust
@@ -111,6 +134,73 @@
 //!
 //! For use of the widget please check examples/demo.rs
 //!
+//! The capture levels per target, as tuned by the user, can be written to a TOML file by
+//! [`save_levels`] and restored by [`load_levels`] on next start.
+//! [`targets`] lists the known targets with their levels, e.g. for an own settings UI.
+//! Stale targets, e.g. of completed subsystems, are dropped by [`remove_target`],
+//! [`retain_targets`] or [`clear_targets`].
+//! For libraries, which use arbitrary strings as target, [`set_level_for_module`] and
+//! `TuiWidgetState::set_level_for_module()` filter by the module path instead.
+//! `TuiWidgetState::set_file_filter()` shows only the events of matching source files.
+//!
+//! Known noise can be dropped before it takes space in the buffer by [`set_capture_filter`]
+//! with a predicate or by [`set_capture_filter_substrings`] with substrings of the messages.
+//! [`set_collapse_duplicates`] folds consecutive identical messages into one event, which is
+//! followed by "last message repeated N times". [`set_sampling_for_target`] keeps only one of
+//! every N debug and trace events of a target. With [`set_severe_retention`], errors and
+//! warnings evicted from the buffer are kept in an additional buffer, so a flood of trace
+//! events does not push them out first. Alternatively [`set_buffer_depth_for_level`] keeps
+//! the events in one buffer per level with individual depths, and
+//! [`set_buffer_depth_per_target`] in one buffer per target with a global cap.
+//! [`set_buffer_memory_limit`] limits the buffer by the approximate memory usage of the events.
+//! With [`set_spill_file`] the evicted events are written to a file instead, and the widget
+//! loads them on scrolling back in page mode.
+//! As a middle ground, `set_compressed_history()` (feature `gzip`) keeps them in memory
+//! as compressed blocks, which are decompressed on scrolling back.
+//! [`stats`] returns the number of received, buffered and lost events, the high-water mark of
+//! the hot buffer and the counts per level, e.g. for a "dropped N" badge or assertions in tests.
+//! A hook set by [`set_lost_events_hook`] is called on each overflow of the hot buffer.
+//! [`set_log_domain`] routes targets by prefix, e.g. `"net::*"`, into a separate buffer with its
+//! own depth and optionally its own sink, which is shown by a widget built with `.domain("net")`.
+//! The display levels of a widget can be configured in the same format by
+//! `TuiWidgetState::from_toml()`, e.g. from a file shipped with the application.
+//! Alternatively `TuiWidgetState::set_display_filter_from_string()` takes the syntax of
+//! RUST_LOG, e.g. `info,my_crate=debug,hyper=warn`. As for RUST_LOG, `my_crate=debug`
+//! applies to the targets below it like `my_crate::db`, too.
+//!
+//! The capture levels accept the same syntax by [`set_env_filter`]. [`reload_env_filter`]
+//! applies the environment variable RUST_LOG, e.g. again after a change at runtime. The
+//! event `TuiWidgetEvent::ReloadFilterKey` does the same and can be bound to a key.
+//!
+//! ## Rendering with state
+//!
+//! All widgets implement `StatefulWidget` with `TuiWidgetState` as state, so they can be
+//! rendered the ratatui way:
+//! ```ignore
+//! frame.render_stateful_widget(TuiLoggerSmartWidget::default(), area, &mut state);
+//! ```
+//! Alternatively the state can still be passed via `.state(&state)` and the widget rendered
+//! with `frame.render_widget()`. As the widgets implement both traits, a direct call of
+//! `render()` needs to be qualified, e.g. `Widget::render(widget, area, buf)`.
+//!
+//! With feature `widget-ref`, the widgets implement ratatui's `WidgetRef`, too. It enables
+//! the feature `unstable-widget-ref` of ratatui, so it is opt-in. Then a widget with all its
+//! styles and formats can be built once and be rendered by reference on every frame:
+//! ```ignore
+//! let widget = TuiLoggerWidget::default()
+//!     .output_level(Some(TuiLoggerLevelOutput::Abbreviated))
+//!     .state(&state);
+//! // in the draw loop
+//! frame.render_widget(&widget, area);
+//! ```
+//!
+//! The filter settings of the state, i.e. display levels per target, hide_off, focus and
+//! the selected target, are returned by `TuiWidgetState::save_config()` as
+//! [`TuiWidgetStateConfig`] and applied again by `restore_config()`. With feature "serde",
+//! the config implements `Serialize` and `Deserialize` to be stored on exit.
+//! The same holds for [`LevelConfig`], which is written as the map `targets` from target
+//! to level together with `default_display_level`.
+//!
 //! ## Demo
 //!
 //! Run demo using termion:
@@ -136,16 +226,121 @@
 //! `tui-logger` provides a [`TuiSlogDrain`] which implements `slog::Drain` and will route all records
 //! it receives to the `tui-logger` widget.
 //!
+//! Values attached to the logger, e.g. by `Logger::new(o!(...))`, are stored as key-value pairs
+//! of the record and by default appended to the message. With [`TuiSlogContext`] they can
+//! be shown as prefix instead or only as key-value pairs, e.g. in columns of the widget.
+//!
+//! slog's `Critical` level is logged as `Error`. [`TuiSlogCritical`] allows to flag these records.
+//!
 //! Enabled by feature "slog-support"
 //!
 //! ## `tracing-subscriber` support
 //!
 //! `tui-logger` provides a [`TuiTracingSubscriberLayer`] which implements
 //! `tracing_subscriber::Layer` and will collect all events
-//! it receives to the `tui-logger` widget
+//! it receives to the `tui-logger` widget. Optionally the creation and the closing
+//! of spans are logged, too.
 //!
 //! Enabled by feature "tracing-support"
 //!
+//! ## Output sinks
+//!
+//! Besides the widgets, the captured events can be written to several sinks at the same time,
+//! which are registered by [`add_sink`] and removed by [`remove_sink`]. For example, a
+//! file with all details and a file with only the messages:
+//! ```rust,no_run
+//! use tui_logger::{add_sink, set_log_file, TuiLoggerFile};
+//!
+//! set_log_file(TuiLoggerFile::new("app.log"));
+//! add_sink(
+//!     TuiLoggerFile::new("messages.log")
+//!         .output_file(false)
+//!         .output_line(false)
+//!         .output_target(false),
+//! );
+//! ```
+//! Own sinks implement the trait [`LogSink`]. A closure can be registered by [`add_event_hook`],
+//! e.g. to count metrics or forward alerts. [`event_receiver`] returns a channel receiver of the
+//! events for an application task, e.g. to show a popup on errors.
+//!
+//! With `TuiLoggerFile::format_json(true)`, each event is written as one JSON object per line,
+//! which tools like jq, Loki or vector consume directly.
+//!
+//! `TuiLoggerFile::new()` appends to an existing file. `TuiLoggerFile::create()` truncates it
+//! instead, so each run starts with a clean file. Alternatively `session_header(true)` writes
+//! a line with start time and pid to separate the runs.
+//!
+//! By default the file sink leaves buffering to the writer. `TuiLoggerFile::flush_on()` selects
+//! flushing per event, per batch of events or in an interval, and `TuiLoggerFile::sync(true)`
+//! additionally syncs the file to disk. Call [`flush_sinks`] before exiting to not lose the
+//! last events, e.g. of a crash. [`shutdown`] does the same, closes the sinks and stops the
+//! background thread, e.g. at the end of the TUI or of a test binary.
+//!
+//! ## Export
+//!
+//! The events in the buffer can be exported by [`export_html`] into a standalone HTML file
+//! with colored levels, e.g. for attaching to bug reports, or by [`export_csv`] as CSV.
+//! Without a widget state, the events of the main buffer and of all log domains are exported.
+//! With the state of a widget, only the events displayed with its filters are exported,
+//! taken from the log domain of the widget last rendered with this state.
+//! `TuiWidgetState::export_visible()` writes these events in the format of the file dump.
+//! [`dump_buffer_to_file`] writes the whole history in the format of the file dump,
+//! even if no log file has been set before.
+//! For own views, analytics or exporters, [`events_snapshot`] returns the buffered events
+//! selected by a filter, and [`with_events`] iterates over them without cloning
+//! while the logger is locked.
+//! [`events_since`] returns only the events added since the previous call, e.g. for polling each frame.
+//! These functions include the events of the log domains.
+//!
+//! ## syslog
+//!
+//! In addition to the file dump, the captured events can be sent as RFC 5424 messages
+//! to a syslog collector via UDP or a local unix socket:
+//! ```rust,no_run
+//! fn main() -> std::io::Result<()> {
+//!     let syslog = tui_logger::TuiLoggerSyslog::new_udp("loghost:514")?
+//!         .app_name("my-daemon")
+//!         .facility(16);
+//!     tui_logger::add_sink(syslog);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! ## Network sink
+//!
+//! The log of a headless instance can be watched remotely with [`TuiLoggerNetSink`],
+//! which streams newline-delimited text or JSON records to a TCP/UDP endpoint
+//! and reconnects after errors:
+//! ```rust
+//! use tui_logger::{add_sink, TuiLoggerNetFormat, TuiLoggerNetSink};
+//!
+//! add_sink(TuiLoggerNetSink::tcp("127.0.0.1:5555").format(TuiLoggerNetFormat::Json));
+//! ```
+//!
+//! ## Remote viewer
+//!
+//! With feature "remote", a running application can serve its events by [`serve_events`].
+//! Another application using `tui-logger` attaches by [`attach_remote`] and shows
+//! the received events in its own widgets, starting with the buffered ones.
+//! ```rust,ignore
+//! // in the service
+//! tui_logger::serve_events("127.0.0.1:5556")?;
+//! // in the viewer
+//! tui_logger::attach_remote("127.0.0.1:5556")?;
+//! ```
+//!
+//! ## OpenTelemetry
+//!
+//! With feature "otel", the captured events are forwarded to an OpenTelemetry logger
+//! by `add_sink(TuiLoggerOtel::from_provider(&provider))`, e.g. with the
+//! `SdkLoggerProvider` of an existing observability pipeline.
+//!
+//! ## journald
+//!
+//! With feature "journald" on unix, the captured events are additionally sent to
+//! systemd-journald by `add_sink(TuiLoggerJournald::new()?)`. The level is mapped to `PRIORITY`
+//! and the source location to `CODE_FILE` and `CODE_LINE`.
+//!
 //! ## Custom filtering
 //! ```rust
 //! #[macro_use]
@@ -166,6 +361,64 @@
 //! }
 //! ```
 //!
+//! ## fern integration
+//!
+//! `fern` needs the global logger slot, too. Because [`Drain`] implements `log::Log`,
+//! it can be chained into a `fern::Dispatch` instead of calling [`init_logger`]:
+//! ```rust
+//! use log::LevelFilter;
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     tui_logger::init_drain()?;
+//!     fern::Dispatch::new()
+//!         .level(LevelFilter::Debug)
+//!         .chain(Box::new(tui_logger::Drain::new()) as Box<dyn log::Log>)
+//!         .chain(std::io::stderr())
+//!         .apply()?;
+//!     log::info!("Logging via fern works!");
+//!     Ok(())
+//! }
+//! ```
+//! [`init_drain`] starts the processing of the events without claiming the global logger slot.
+//!
+//! As [`init_logger`] succeeds only once per process, test binaries with several tests can
+//! use [`init_logger_lenient`] in each test, and [`reset_for_tests`] to start from a clean state.
+//!
+//! On wasm32 and hosts without threads, no background thread is spawned (or by
+//! `TuiLoggerOptions::mover_thread(false)` or [`init_logger_no_mover`]). Then the widgets move
+//! the events on rendering, and [`move_events`] can be called by the application, e.g. from its
+//! event loop tick. Until then the events wait in the hot buffer, whose depth has to cover the
+//! events of the longest interval between the calls. [`move_events`] returns the number of moved
+//! and lost events of the batch, so the application can react on losses.
+//!
+//! ## Ingestion of piped input
+//!
+//! A log viewer for `some_process | my-tui-viewer` is built by [`ingest_stdin`],
+//! which spawns a thread converting the lines of stdin into records by a [`LineParser`].
+//! Closures `FnMut(&str) -> Option<ExtLogRecord>` can be used as parser, too.
+//! Parsers for logfmt ([`LogfmtParser`]) and for JSON lines ([`JsonLinesParser`] with feature
+//! "json-lines") map the usual fields like level, target and message to the record.
+//! ```rust,no_run
+//! tui_logger::init_drain().unwrap();
+//! tui_logger::ingest_stdin(tui_logger::PlainLineParser::default()).unwrap();
+//! ```
+//!
+//! ## defmt frames
+//!
+//! Host-side tools for embedded targets can feed decoded defmt frames by [`push_defmt_frame`].
+//! The module path of the frame is used as target and the firmware timestamp is stored
+//! as key-value pair `device_time`, which can be shown as column of the widget.
+//! ```rust
+//! tui_logger::push_defmt_frame(&tui_logger::DefmtFrame {
+//!     level: Some(log::Level::Warn),
+//!     timestamp: Some("12.000305"),
+//!     message: "battery low",
+//!     file: Some("src/main.rs"),
+//!     line: Some(42),
+//!     module_path: Some("firmware::power"),
+//! });
+//! ```
+//!
 //! ## Custom formatting
 //!
 //! For experts only ! Configure with along the lines:
@@ -189,58 +442,221 @@ extern crate lazy_static;
 
 use std::collections::hash_map::Iter;
 use std::collections::hash_map::Keys;
-use std::collections::HashMap;
-use std::io::Write;
+use std::collections::{HashMap, HashSet};
 use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{fence, AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 
-use chrono::{DateTime, Local};
-use log::{Level, Log, Metadata, Record, SetLoggerError};
-use parking_lot::Mutex;
+use crossbeam_queue::ArrayQueue;
+use log::{Log, Metadata, Record, SetLoggerError};
+use parking_lot::{Mutex, RwLock};
+#[cfg(feature = "widget-ref")]
+use ratatui::widgets::WidgetRef;
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
     style::{Modifier, Style},
-    widgets::{Block, Widget},
+    widgets::{Block, StatefulWidget, Widget},
 };
+use store::EventStore;
 use widget::inner::TuiLoggerInner;
 use widget::inner::TuiWidgetInnerState;
 
+mod ansi;
 mod circular;
+mod defmt;
+mod domain;
+mod export;
+mod ingest;
+mod levels;
+#[cfg(feature = "otel")]
+#[cfg_attr(docsrs, doc(cfg(feature = "otel")))]
+mod otel;
+#[cfg(feature = "remote")]
+#[cfg_attr(docsrs, doc(cfg(feature = "remote")))]
+mod remote;
+mod sink;
 #[cfg(feature = "slog-support")]
 #[cfg_attr(docsrs, doc(cfg(feature = "slog-support")))]
 mod slog;
+mod spill;
+mod store;
+mod timestamp;
 #[cfg(feature = "tracing-support")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tracing-support")))]
 mod tracing_subscriber;
 
 pub use crate::circular::CircularBuffer;
+pub use crate::defmt::{push_defmt_frame, DefmtFrame, DEFMT_TARGET};
+pub use crate::domain::{remove_log_domain, set_log_domain, set_log_domain_sink};
+pub use crate::export::{
+    dump_buffer, dump_buffer_to_file, events_since, events_snapshot, export_csv, export_html,
+    with_events,
+};
+#[cfg(feature = "json-lines")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json-lines")))]
+pub use crate::ingest::JsonLinesParser;
+pub use crate::ingest::{
+    ingest_reader, ingest_stdin, LineParser, LogfmtParser, PlainLineParser, STDIN_TARGET,
+};
+pub use crate::levels::{load_levels, reload_env_filter, save_levels, set_env_filter};
+#[cfg(feature = "otel")]
+#[cfg_attr(docsrs, doc(cfg(feature = "otel")))]
+pub use crate::otel::TuiLoggerOtel;
+#[cfg(feature = "remote")]
+#[cfg_attr(docsrs, doc(cfg(feature = "remote")))]
+pub use crate::remote::{attach_remote, serve_events};
+use crate::sink::{ChannelSink, EventHook, EVENT_CHANNEL_DEPTH};
+pub use crate::sink::{LogSink, SinkId};
 #[cfg(feature = "slog-support")]
 #[cfg_attr(docsrs, doc(cfg(feature = "slog-support")))]
-pub use crate::slog::TuiSlogDrain;
+pub use crate::slog::{TuiSlogContext, TuiSlogCritical, TuiSlogDrain};
+pub use crate::timestamp::{Clock, SystemClock, Timestamp, TuiLoggerTimezone};
 #[cfg(feature = "tracing-support")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tracing-support")))]
 pub use crate::tracing_subscriber::TuiTracingSubscriberLayer;
 #[doc(no_inline)]
-pub use log::LevelFilter;
+pub use log::{Level, LevelFilter};
 
 pub mod widget;
-pub use widget::inner::TuiWidgetState;
+pub use widget::inner::{TuiWidgetState, TuiWidgetStateConfig};
 pub use widget::logformatter::LogFormatter;
 pub use widget::smart::TuiLoggerSmartWidget;
 pub use widget::standard::TuiLoggerWidget;
 
 pub mod file;
-pub use file::TuiLoggerFile;
+pub use file::{TuiLoggerFile, TuiLoggerFileErrorPolicy, TuiLoggerFileFlush};
+
+pub mod syslog;
+pub use syslog::TuiLoggerSyslog;
+
+pub mod net;
+pub use net::{TuiLoggerNetFormat, TuiLoggerNetProtocol, TuiLoggerNetSink};
+
+#[cfg(all(feature = "journald", unix))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "journald", unix))))]
+pub mod journald;
+#[cfg(all(feature = "journald", unix))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "journald", unix))))]
+pub use journald::TuiLoggerJournald;
 
+pub mod event;
+pub use event::{TuiWidgetKey, TuiWidgetKeyMap};
+
+#[derive(Clone)]
 pub struct ExtLogRecord {
-    pub timestamp: DateTime<Local>,
+    /// Sequence number, which is assigned by move_events() in order of the events
+    pub seq: u64,
+    pub timestamp: Timestamp,
+    /// Id of the process, which has logged the event, if known
+    pub pid: Option<u32>,
     pub level: Level,
     pub target: String,
+    /// Module path of the event, if known
+    pub module_path: Option<String>,
     pub file: String,
     pub line: u32,
     pub msg: String,
+    /// Key-value pairs of the event. The log crate's pairs are captured with feature `kv`.
+    pub key_values: Vec<(String, String)>,
+    /// Names of the enclosing tracing spans, from the outermost to the innermost
+    pub spans: Vec<String>,
+    /// Duration of the span, for the entries logged on closing of a span
+    pub span_duration: Option<Duration>,
+    /// Backtrace of error level events, captured with feature `backtrace-support`
+    pub backtrace: Option<String>,
+}
+impl ExtLogRecord {
+    /// Create a record, e.g. to inject it by push_record().
+    /// File and line are unknown and can be set by location().
+    pub fn new(timestamp: Timestamp, level: Level, target: &str, msg: &str) -> ExtLogRecord {
+        ExtLogRecord {
+            seq: 0,
+            timestamp,
+            pid: None,
+            level,
+            target: target.to_string(),
+            module_path: None,
+            file: "?".to_string(),
+            line: 0,
+            msg: msg.to_string(),
+            key_values: vec![],
+            spans: vec![],
+            span_duration: None,
+            backtrace: None,
+        }
+    }
+    /// Set the source location of the event
+    pub fn location(mut self, file: &str, line: u32) -> ExtLogRecord {
+        self.file = file.to_string();
+        self.line = line;
+        self
+    }
+    /// Approximate memory usage of the record in bytes, including its heap allocations
+    pub fn memory_usage(&self) -> usize {
+        mem::size_of::<ExtLogRecord>()
+            + self.target.capacity()
+            + self.module_path.as_ref().map_or(0, |path| path.capacity())
+            + self.file.capacity()
+            + self.msg.capacity()
+            + self
+                .key_values
+                .iter()
+                .map(|(key, value)| {
+                    mem::size_of::<(String, String)>() + key.capacity() + value.capacity()
+                })
+                .sum::<usize>()
+            + self
+                .spans
+                .iter()
+                .map(|span| mem::size_of::<String>() + span.capacity())
+                .sum::<usize>()
+            + self
+                .backtrace
+                .as_ref()
+                .map_or(0, |backtrace| backtrace.capacity())
+    }
+    /// Same level, target and message
+    fn is_duplicate(&self, other: &ExtLogRecord) -> bool {
+        self.level == other.level && self.target == other.target && self.msg == other.msg
+    }
+    /// Set the module path of the event
+    pub fn module_path(mut self, module_path: &str) -> ExtLogRecord {
+        self.module_path = Some(module_path.to_string());
+        self
+    }
+    /// Set the id of the process, which has logged the event
+    pub fn pid(mut self, pid: u32) -> ExtLogRecord {
+        self.pid = Some(pid);
+        self
+    }
+    /// Add a key-value pair
+    pub fn key_value(mut self, key: &str, value: &str) -> ExtLogRecord {
+        self.key_values.push((key.to_string(), value.to_string()));
+        self
+    }
+    /// Describe a closed span, e.g. `[span http_request took 42.0ms]`.
+    /// Returns None, if the entry is not logged on closing of a span.
+    pub fn span_summary(&self) -> Option<String> {
+        let duration = self.span_duration?;
+        let micros = i64::try_from(duration.as_micros()).unwrap_or(i64::MAX);
+        Some(format!(
+            "[span {} took {}]",
+            self.spans.last().map(|name| name.as_str()).unwrap_or("?"),
+            format_duration(micros)
+        ))
+    }
+    /// Get the value of the first pair with the given key
+    pub fn get_value(&self, key: &str) -> Option<&str> {
+        self.key_values
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
 }
 
 fn advance_levelfilter(levelfilter: LevelFilter) -> (Option<LevelFilter>, Option<LevelFilter>) {
@@ -260,13 +676,37 @@ fn advance_levelfilter(levelfilter: LevelFilter) -> (Option<LevelFilter>, Option
 /// a widget's LevelConfig. In order to detect changes, the generation
 /// of the hash table is compared with any previous copied table.
 /// On every change the generation is incremented.
+///
+/// With feature "serde", the levels per target and the default display level
+/// can be (de)serialized, e.g. as part of the application's configuration file.
 #[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct LevelConfig {
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "targets", serialize_with = "serialize_sorted")
+    )]
     config: HashMap<String, LevelFilter>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     generation: u64,
+    #[cfg_attr(feature = "serde", serde(skip))]
     origin_generation: u64,
     default_display_level: Option<LevelFilter>,
+    /// Levelfilters for the targets below a target, e.g. `my_crate` for `my_crate::db`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    directives: Vec<(String, LevelFilter)>,
+}
+
+/// Write the targets in sorted order, so the configuration file is stable
+#[cfg(feature = "serde")]
+fn serialize_sorted<S: serde::Serializer>(
+    config: &HashMap<String, LevelFilter>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(config.iter().collect::<std::collections::BTreeMap<_, _>>())
 }
+
 impl LevelConfig {
     /// Create an empty LevelConfig.
     pub fn new() -> LevelConfig {
@@ -275,6 +715,7 @@ impl LevelConfig {
             generation: 0,
             origin_generation: 0,
             default_display_level: None,
+            directives: vec![],
         }
     }
     /// Set for a given target the LevelFilter in the table and update the generation.
@@ -289,12 +730,58 @@ impl LevelConfig {
         self.config.insert(target.to_string(), level);
         self.generation += 1;
     }
+    /// Remove the target from the table and update the generation.
+    /// Returns the levelfilter of the target, if it has been in the table.
+    pub fn remove(&mut self, target: &str) -> Option<LevelFilter> {
+        let levelfilter = self.config.remove(target)?;
+        self.generation += 1;
+        Some(levelfilter)
+    }
+    /// Remove all targets from the table and update the generation.
+    pub fn clear(&mut self) {
+        if !self.config.is_empty() {
+            self.config.clear();
+            self.generation += 1;
+        }
+    }
+    /// Keep only the targets, for which the predicate returns true, and update the generation.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&str, LevelFilter) -> bool,
+    {
+        let len = self.config.len();
+        self.config
+            .retain(|target, levelfilter| f(target, *levelfilter));
+        if self.config.len() != len {
+            self.generation += 1;
+        }
+    }
+    /// Apply the levelfilters of the directives to the targets below them, too, like RUST_LOG.
+    /// The most specific directive wins. Known targets are updated, and new targets take
+    /// the level on merging.
+    pub(crate) fn set_directives(&mut self, directives: Vec<(String, LevelFilter)>) {
+        self.directives
+            .retain(|(prefix, _)| !directives.iter().any(|(target, _)| target == prefix));
+        self.directives.extend(directives);
+        let targets: Vec<(String, LevelFilter)> = self
+            .config
+            .keys()
+            .filter_map(|target| Some((target.clone(), self.directive_level(target)?)))
+            .collect();
+        for (target, levelfilter) in targets {
+            self.set(&target, levelfilter);
+        }
+    }
+    /// The levelfilter of the most specific directive matching the target
+    pub(crate) fn directive_level(&self, target: &str) -> Option<LevelFilter> {
+        levels::directive_level(&self.directives, target)
+    }
     /// Set default display level filter for new targets - independent from recording
     pub fn set_default_display_level(&mut self, level: LevelFilter) {
         self.default_display_level = Some(level);
     }
     /// Retrieve an iter for all the targets stored in the hash table.
-    pub fn keys(&self) -> Keys<String, LevelFilter> {
+    pub fn keys(&self) -> Keys<'_, String, LevelFilter> {
         self.config.keys()
     }
     /// Get the levelfilter for a given target.
@@ -302,7 +789,7 @@ impl LevelConfig {
         self.config.get(target).cloned()
     }
     /// Retrieve an iterator through all entries of the table.
-    pub fn iter(&self) -> Iter<String, LevelFilter> {
+    pub fn iter(&self) -> Iter<'_, String, LevelFilter> {
         self.config.iter()
     }
     /// Merge an origin LevelConfig into this one.
@@ -319,7 +806,8 @@ impl LevelConfig {
                     }
                 }
                 let levelfilter = self
-                    .default_display_level
+                    .directive_level(target)
+                    .or(self.default_display_level)
                     .map(|lvl| {
                         if lvl > *origin_levelfilter {
                             *origin_levelfilter
@@ -338,131 +826,305 @@ impl LevelConfig {
 /// These are the sub-structs for the static TUI_LOGGER struct.
 struct HotSelect {
     hashtable: HashMap<u64, LevelFilter>,
+    /// Levelfilters per module path, which take precedence over the target's
+    modules: HashMap<u64, LevelFilter>,
+    /// Directives of the env filter for the targets without own levelfilter
+    env_directives: Vec<(String, LevelFilter)>,
     default: LevelFilter,
 }
+/// The logging threads push into the queues without an exclusive lock.
+/// Only a change of the hot buffer depth or the number of shards takes the write lock.
 struct HotLog {
-    events: CircularBuffer<ExtLogRecord>,
-    mover_thread: Option<thread::JoinHandle<()>>,
+    shards: RwLock<Vec<HotShard>>,
+    /// Number of events pushed out of the full queues, since the last move_events()
+    lost: AtomicUsize,
+    /// Set while the mover thread waits for the first event to arrive
+    mover_idle: AtomicBool,
+    /// Requests the mover thread to exit
+    mover_stop: AtomicBool,
+    /// Set without mover thread, so the widgets move the events on rendering
+    manual_move: AtomicBool,
+    mover_thread: Mutex<Option<thread::JoinHandle<()>>>,
+    clock: RwLock<Box<dyn Clock>>,
+}
+
+struct HotShard {
+    events: ArrayQueue<ExtLogRecord>,
+    pushed: AtomicUsize,
+    /// The mover thread is woken after each this number of pushed events
+    wake_at: usize,
+}
+impl HotShard {
+    fn new(depth: usize, wake_at: usize) -> HotShard {
+        HotShard {
+            events: ArrayQueue::new(depth),
+            pushed: AtomicUsize::new(0),
+            wake_at,
+        }
+    }
+}
+
+thread_local! {
+    /// The threads are assigned to the shards of the hot buffer in turn
+    static HOT_SHARD: usize = {
+        static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+        NEXT_SHARD.fetch_add(1, Ordering::Relaxed)
+    };
 }
 
 struct TuiLogger {
     hot_select: Mutex<HotSelect>,
-    hot_log: Mutex<HotLog>,
+    hot_log: HotLog,
     inner: Mutex<TuiLoggerInner>,
+    start_time: Mutex<Timestamp>,
+    timezone: Mutex<TuiLoggerTimezone>,
 }
 impl TuiLogger {
-    pub fn move_events(&self) {
+    pub fn move_events(&self) -> TuiLoggerMoveSummary {
         // If there are no new events, then just return
-        if self.hot_log.lock().events.total_elements() == 0 {
-            return;
+        if self.hot_is_empty() {
+            return TuiLoggerMoveSummary::default();
         }
-        // Exchange new event buffer with the hot buffer
-        let mut received_events = {
-            let hot_depth = self.inner.lock().hot_depth;
-            let new_circular = CircularBuffer::new(hot_depth);
-            let mut hl = self.hot_log.lock();
-            mem::replace(&mut hl.events, new_circular)
-        };
+        self.update_hot();
+        // Take at most one queue full per shard, so busy logging threads cannot keep this running
+        let mut consumed = vec![];
+        {
+            let shards = self.hot_log.shards.read();
+            for shard in shards.iter() {
+                for _ in 0..shard.events.capacity() {
+                    match shard.events.pop() {
+                        Some(log_entry) => consumed.push(log_entry),
+                        None => break,
+                    }
+                }
+            }
+            if shards.len() > 1 {
+                // Merge the shards. The sort is stable, so each thread's order is kept.
+                consumed.sort_by_key(|log_entry| log_entry.timestamp);
+            }
+        }
+        let lost = self.hot_log.lost.swap(0, Ordering::Relaxed);
         let mut tli = self.inner.lock();
-        let total = received_events.total_elements();
-        let elements = received_events.len();
+        let elements = consumed.len();
+        let total = elements + lost;
         tli.total_events += total;
-        let mut consumed = received_events.take();
-        let mut reversed = Vec::with_capacity(consumed.len() + 1);
+        tli.lost_events += lost;
+        tli.hot_high_water = tli.hot_high_water.max(elements);
+        let mut reversed = Vec::with_capacity(elements + 1);
         while let Some(log_entry) = consumed.pop() {
             reversed.push(log_entry);
         }
-        if total > elements {
+        let now = self.now();
+        let previous_move = mem::replace(&mut tli.last_move, now);
+        // The lost events have been pushed out by the oldest received one
+        let lost_events = (lost > 0).then(|| TuiLoggerLostEvents {
+            lost,
+            recorded: elements,
+            from: previous_move,
+            to: reversed.last().map_or(now, |log_entry| log_entry.timestamp),
+        });
+        if lost > 0 {
             // Too many events received, so some have been lost
             let new_log_entry = ExtLogRecord {
-                timestamp: reversed[reversed.len() - 1].timestamp,
+                seq: 0,
+                timestamp: reversed
+                    .last()
+                    .map_or_else(|| self.now(), |log_entry| log_entry.timestamp),
+                pid: Some(std::process::id()),
                 level: Level::Warn,
                 target: "TuiLogger".to_string(),
+                module_path: None,
                 file: "?".to_string(),
                 line: 0,
+                key_values: vec![],
+                spans: vec![],
+                span_duration: None,
+                backtrace: None,
                 msg: format!(
                     "There have been {} events lost, {} recorded out of {}",
-                    total - elements,
-                    elements,
-                    total
+                    lost, elements, total
                 ),
             };
             reversed.push(new_log_entry);
         }
-        let default_level = tli.default;
         while let Some(log_entry) = reversed.pop() {
-            if tli.targets.get(&log_entry.target).is_none() {
-                tli.targets.set(&log_entry.target, default_level);
+            tli.capture(log_entry);
+        }
+        tli.end_batch();
+        // The hook is called without the lock, so it may use the API of the logger
+        let lost_events_hook = tli.lost_events_hook.clone();
+        drop(tli);
+        if let (Some(hook), Some(lost_events)) = (lost_events_hook, lost_events) {
+            hook(&lost_events);
+        }
+        TuiLoggerMoveSummary {
+            moved: elements,
+            lost,
+        }
+    }
+}
+impl TuiLoggerInner {
+    /// Apply the capture filter, the sampling and the folding of duplicates to the event
+    /// moved from the hot buffer and store it
+    fn capture(&mut self, log_entry: ExtLogRecord) {
+        if let Some(filter) = self.capture_filter.as_ref() {
+            if !filter(&log_entry) {
+                return;
             }
-            if let Some(ref mut file_options) = tli.dump {
-                let mut output = String::new();
-                let (lev_long, lev_abbr, with_loc) = match log_entry.level {
-                    log::Level::Error => ("ERROR", "E", true),
-                    log::Level::Warn => ("WARN ", "W", true),
-                    log::Level::Info => ("INFO ", "I", false),
-                    log::Level::Debug => ("DEBUG", "D", true),
-                    log::Level::Trace => ("TRACE", "T", true),
-                };
-                if let Some(fmt) = file_options.timestamp_fmt.as_ref() {
-                    output.push_str(&format!("{}", log_entry.timestamp.format(fmt)));
-                    output.push(file_options.format_separator);
-                }
-                match file_options.format_output_level {
-                    None => {}
-                    Some(TuiLoggerLevelOutput::Abbreviated) => {
-                        output.push_str(lev_abbr);
-                        output.push(file_options.format_separator);
-                    }
-                    Some(TuiLoggerLevelOutput::Long) => {
-                        output.push_str(lev_long);
-                        output.push(file_options.format_separator);
-                    }
-                }
-                if file_options.format_output_target {
-                    output.push_str(&log_entry.target);
-                    output.push(file_options.format_separator);
-                }
-                if with_loc {
-                    if file_options.format_output_file {
-                        output.push_str(&log_entry.file);
-                        output.push(file_options.format_separator);
-                    }
-                    if file_options.format_output_line {
-                        output.push_str(&format!("{}", log_entry.line));
-                        output.push(file_options.format_separator);
-                    }
+        }
+        if log_entry.level >= Level::Debug {
+            if let Some((every, count)) = self.sampling.get_mut(&log_entry.target) {
+                *count += 1;
+                if (*count - 1) % *every != 0 {
+                    return;
                 }
-                output.push_str(&log_entry.msg);
-                if let Err(_e) = writeln!(file_options.dump, "{}", output) {
-                    // TODO: What to do in case of write error ?
+            }
+        }
+        if self.collapse_duplicates {
+            if self
+                .last_event
+                .as_ref()
+                .is_some_and(|last| last.is_duplicate(&log_entry))
+            {
+                self.duplicates += 1;
+                self.last_duplicate = Some(log_entry);
+                return;
+            }
+            // The summary of the ended run is stored before this event
+            self.store_duplicates();
+            self.last_event = Some(log_entry.clone());
+        }
+        self.store(log_entry);
+    }
+    /// Complete the batch moved from the hot buffer. A pending run of duplicates is
+    /// summarized now, so the widgets do not wait for the next different event.
+    fn end_batch(&mut self) {
+        self.store_duplicates();
+        for (_, sink) in self.sinks.iter_mut() {
+            sink.end_batch();
+        }
+        for domain in self.domains.iter_mut() {
+            if let Some(sink) = domain.sink.as_mut() {
+                sink.end_batch();
+            }
+        }
+    }
+    /// Store the event "last message repeated N times" for the pending duplicates
+    fn store_duplicates(&mut self) {
+        if let Some(mut summary) = self.last_duplicate.take() {
+            summary.msg = format!("last message repeated {} times", self.duplicates);
+            self.duplicates = 0;
+            self.store(summary);
+        }
+    }
+    /// Number the event, write it to the sinks and push it into its buffer
+    fn store(&mut self, mut log_entry: ExtLogRecord) {
+        log_entry.seq = self.next_seq;
+        self.next_seq += 1;
+        self.level_counts[log_entry.level as usize - 1] += 1;
+        if self.strip_ansi {
+            log_entry.msg = ansi::strip_ansi(&log_entry.msg);
+        }
+        if self.targets.get(&log_entry.target).is_none() {
+            let levelfilter = self.inherited_level(&log_entry.target);
+            self.targets.set(&log_entry.target, levelfilter);
+            self.removed_targets.remove(&log_entry.target);
+        }
+        for (_, sink) in self.sinks.iter_mut() {
+            sink.write(&log_entry);
+        }
+        #[cfg(feature = "remote")]
+        remote::publish(&mut self.remote_clients, &log_entry);
+        match self
+            .domains
+            .iter_mut()
+            .find(|domain| domain.matches(&log_entry.target))
+        {
+            Some(domain) => {
+                if let Some(sink) = domain.sink.as_mut() {
+                    sink.write(&log_entry);
                 }
+                domain.events.push(log_entry);
             }
-            tli.events.push(log_entry);
+            None => self.events.push(log_entry),
+        }
+    }
+    /// Capture level of a target without own levelfilter
+    fn inherited_level(&self, target: &str) -> LevelFilter {
+        levels::directive_level(&self.env_filter_targets, target).unwrap_or(self.default)
+    }
+    /// The events of the domain, or of the main buffer without domain
+    pub(crate) fn domain_events(&mut self, domain: Option<&str>) -> &mut EventStore {
+        match domain.and_then(|name| self.domains.iter_mut().find(|domain| domain.name == name)) {
+            Some(domain) => &mut domain.events,
+            None => &mut self.events,
         }
     }
+    /// The main buffer and the buffers of all domains
+    pub(crate) fn all_stores(&mut self) -> impl Iterator<Item = &mut EventStore> {
+        std::iter::once(&mut self.events)
+            .chain(self.domains.iter_mut().map(|domain| &mut domain.events))
+    }
+}
+
+/// The initial state of the logger
+fn new_inner() -> TuiLoggerInner {
+    TuiLoggerInner {
+        hot_depth: 1000,
+        hot_shards: 1,
+        move_latency: Duration::from_millis(10),
+        wake_fraction: 0.5,
+        events: EventStore::new(10000),
+        total_events: 0,
+        lost_events: 0,
+        hot_high_water: 0,
+        level_counts: [0; 5],
+        last_move: timestamp::now(),
+        lost_events_hook: None,
+        sinks: vec![],
+        next_sink_id: 0,
+        log_file: None,
+        #[cfg(feature = "remote")]
+        remote_clients: vec![],
+        default: LevelFilter::Info,
+        targets: LevelConfig::new(),
+        removed_targets: HashSet::new(),
+        strip_ansi: false,
+        next_seq: 0,
+        env_filter_targets: vec![],
+        capture_filter: None,
+        sampling: HashMap::new(),
+        collapse_duplicates: false,
+        last_event: None,
+        duplicates: 0,
+        last_duplicate: None,
+        domains: vec![],
+    }
 }
+
 lazy_static! {
     static ref TUI_LOGGER: TuiLogger = {
         let hs = HotSelect {
             hashtable: HashMap::with_capacity(1000),
+            modules: HashMap::new(),
+            env_directives: vec![],
             default: LevelFilter::Info,
         };
         let hl = HotLog {
-            events: CircularBuffer::new(1000),
-            mover_thread: None,
-        };
-        let tli = TuiLoggerInner {
-            hot_depth: 1000,
-            events: CircularBuffer::new(10000),
-            total_events: 0,
-            dump: None,
-            default: LevelFilter::Info,
-            targets: LevelConfig::new(),
+            shards: RwLock::new(vec![HotShard::new(1000, 500)]),
+            lost: AtomicUsize::new(0),
+            mover_idle: AtomicBool::new(false),
+            mover_stop: AtomicBool::new(false),
+            manual_move: AtomicBool::new(false),
+            mover_thread: Mutex::new(None),
+            clock: RwLock::new(Box::new(SystemClock)),
         };
         TuiLogger {
             hot_select: Mutex::new(hs),
-            hot_log: Mutex::new(hl),
-            inner: Mutex::new(tli),
+            hot_log: hl,
+            inner: Mutex::new(new_inner()),
+            start_time: Mutex::new(timestamp::now()),
+            timezone: Mutex::new(TuiLoggerTimezone::Local),
         }
     };
 }
@@ -496,38 +1158,346 @@ impl std::fmt::Display for TuiLoggerError {
     }
 }
 
+/// Options for the processing of the events, which are set by [`init_logger_with`]
+/// or [`init_drain_with`].
+#[derive(Debug, Clone, Copy)]
+pub struct TuiLoggerOptions {
+    /// Maximum delay, by which new events are moved from the hot buffer into the main buffer.
+    /// See [`set_max_move_latency`].
+    pub max_move_latency: Duration,
+    /// Fill level of the hot buffer as fraction of its depth, at which the mover thread is
+    /// woken before the latency has passed. Smaller values cause more wakeups and lower
+    /// the risk of losing events in bursts.
+    pub wake_fraction: f32,
+    /// Spawn the mover thread. Without it, the events are moved on rendering a widget
+    /// or by calling [`move_events`], e.g. on wasm32 or hosts without threads.
+    ///
+    /// Default is true, except on wasm32
+    pub mover_thread: bool,
+}
+impl Default for TuiLoggerOptions {
+    fn default() -> Self {
+        TuiLoggerOptions {
+            max_move_latency: Duration::from_millis(10),
+            wake_fraction: 0.5,
+            mover_thread: MOVER_THREAD_DEFAULT,
+        }
+    }
+}
+impl TuiLoggerOptions {
+    pub fn max_move_latency(mut self, latency: Duration) -> Self {
+        self.max_move_latency = latency;
+        self
+    }
+    pub fn wake_fraction(mut self, fraction: f32) -> Self {
+        self.wake_fraction = fraction;
+        self
+    }
+    pub fn mover_thread(mut self, enabled: bool) -> Self {
+        self.mover_thread = enabled;
+        self
+    }
+    fn apply(&self) {
+        {
+            let mut tli = TUI_LOGGER.inner.lock();
+            tli.move_latency = self.max_move_latency;
+            tli.wake_fraction = self.wake_fraction;
+        }
+        TUI_LOGGER.update_hot();
+    }
+}
+
+/// wasm32 has no threads
+const MOVER_THREAD_DEFAULT: bool = !cfg!(target_arch = "wasm32");
+
 /// Init the logger.
 pub fn init_logger(max_level: LevelFilter) -> Result<(), TuiLoggerError> {
-    let join_handle = thread::Builder::new()
-        .name("tui-logger::move_events".into())
-        .spawn(|| {
-            let duration = std::time::Duration::from_millis(10);
-            loop {
-                thread::park_timeout(duration);
-                TUI_LOGGER.move_events();
-            }
-        })
-        .map_err(|err| TuiLoggerError::ThreadError(err))?;
-    TUI_LOGGER.hot_log.lock().mover_thread = Some(join_handle);
+    init_drain()?;
+    register_logger(max_level)
+}
+
+/// Init the logger without background thread, e.g. to call [`move_events`] from the
+/// application's own event loop tick.
+///
+/// The widgets move the events on rendering, so they show all events of the frame.
+/// Between the calls, the events wait in the hot buffer, which loses the oldest events,
+/// if it overflows. Its depth can be raised by [`set_hot_buffer_depth`]. The sinks, e.g.
+/// the file dump, receive the events only when moved, too.
+pub fn init_logger_no_mover(max_level: LevelFilter) -> Result<(), TuiLoggerError> {
+    start(false)?;
+    register_logger(max_level)
+}
+
+fn register_logger(max_level: LevelFilter) -> Result<(), TuiLoggerError> {
     if cfg!(feature = "tracing-support") {
         set_default_level(max_level);
         Ok(())
     } else {
         log::set_max_level(max_level);
-        log::set_logger(&*TUI_LOGGER).map_err(|err| TuiLoggerError::SetLoggerError(err))
+        log::set_logger(&*TUI_LOGGER).map_err(TuiLoggerError::SetLoggerError)
     }
 }
 
+/// Init the logger with the given options.
+pub fn init_logger_with(
+    max_level: LevelFilter,
+    options: TuiLoggerOptions,
+) -> Result<(), TuiLoggerError> {
+    init_drain_with(options)?;
+    register_logger(max_level)
+}
+
+/// Init the processing of the events without registering as global logger, with the given
+/// options. See [`init_drain`].
+pub fn init_drain_with(options: TuiLoggerOptions) -> Result<(), TuiLoggerError> {
+    options.apply();
+    start(options.mover_thread)
+}
+
+/// Init the processing of the events without registering as global logger.
+///
+/// This is needed, if the events are fed only by a [`Drain`], e.g. chained into `fern`.
+pub fn init_drain() -> Result<(), TuiLoggerError> {
+    start(MOVER_THREAD_DEFAULT)
+}
+
+fn start(mover_thread: bool) -> Result<(), TuiLoggerError> {
+    *TUI_LOGGER.start_time.lock() = TUI_LOGGER.now();
+    if mover_thread {
+        *TUI_LOGGER.hot_log.mover_thread.lock() = Some(spawn_mover()?);
+    }
+    TUI_LOGGER
+        .hot_log
+        .manual_move
+        .store(!mover_thread, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Overrun of the hot buffer reported to the hook set by [`set_lost_events_hook`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TuiLoggerLostEvents {
+    /// Number of lost events
+    pub lost: usize,
+    /// Number of events received in the same batch
+    pub recorded: usize,
+    /// The events have been lost between these timestamps
+    pub from: Timestamp,
+    pub to: Timestamp,
+}
+
+/// Call the hook, whenever events have been lost by overflow of the hot buffer, e.g. to show
+/// a warning in the status bar or to count them in metrics. The inline warning event is
+/// stored, too. The hook is called by the thread moving the events, without the logger locked.
+pub fn set_lost_events_hook(hook: impl Fn(&TuiLoggerLostEvents) + Send + Sync + 'static) {
+    TUI_LOGGER.inner.lock().lost_events_hook = Some(Arc::new(hook));
+}
+
+/// Remove the hook set by [`set_lost_events_hook`]
+pub fn clear_lost_events_hook() {
+    TUI_LOGGER.inner.lock().lost_events_hook = None;
+}
+
+/// Result of [`move_events`] for one batch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TuiLoggerMoveSummary {
+    /// Number of events taken from the hot buffer. Events dropped afterwards, e.g. by the
+    /// capture filter, are included.
+    pub moved: usize,
+    /// Number of events lost by overflow of the hot buffer since the previous batch
+    pub lost: usize,
+}
+
+/// Move the captured events into the buffer and write them to the sinks.
+/// This is needed only without mover thread, see [`TuiLoggerOptions::mover_thread`],
+/// if no widget is rendered, e.g. for the file dump.
+///
+/// The summary allows to react on lost events, e.g. by raising [`set_hot_buffer_depth`]
+/// or by calling this more often.
+pub fn move_events() -> TuiLoggerMoveSummary {
+    TUI_LOGGER.move_events()
+}
+
+/// Init the logger like [`init_logger`], but succeed, if tui-logger is already initialized,
+/// e.g. by a previous test in the same binary. The mover thread is reused.
+/// An error is returned only, if another logger has been registered.
+///
+/// Together with [`reset_for_tests`], each test can start from a clean state.
+pub fn init_logger_lenient(max_level: LevelFilter) -> Result<(), TuiLoggerError> {
+    {
+        let mut mover_thread = TUI_LOGGER.hot_log.mover_thread.lock();
+        if mover_thread.is_none() && !TUI_LOGGER.hot_log.manual_move.load(Ordering::Relaxed) {
+            *TUI_LOGGER.start_time.lock() = TUI_LOGGER.now();
+            if MOVER_THREAD_DEFAULT {
+                *mover_thread = Some(spawn_mover()?);
+            } else {
+                TUI_LOGGER
+                    .hot_log
+                    .manual_move
+                    .store(true, Ordering::Relaxed);
+            }
+        }
+    }
+    if cfg!(feature = "tracing-support") {
+        set_default_level(max_level);
+        return Ok(());
+    }
+    log::set_max_level(max_level);
+    match log::set_logger(&*TUI_LOGGER) {
+        Err(_) if std::ptr::addr_eq(log::logger(), &*TUI_LOGGER) => Ok(()),
+        result => result.map_err(TuiLoggerError::SetLoggerError),
+    }
+}
+
+/// Reset the logger to its initial state, e.g. between tests sharing the process:
+/// All events are dropped, and the levels, sinks, buffer layouts and the other settings
+/// are set back to their defaults. The logger stays registered and the mover thread keeps
+/// running. As the state is global, tests relying on it must not run concurrently.
+pub fn reset_for_tests() {
+    for shard in TUI_LOGGER.hot_log.shards.read().iter() {
+        while shard.events.pop().is_some() {}
+    }
+    TUI_LOGGER.hot_log.lost.store(0, Ordering::Relaxed);
+    *TUI_LOGGER.hot_log.clock.write() = Box::new(SystemClock);
+    *TUI_LOGGER.timezone.lock() = TuiLoggerTimezone::Local;
+    // The previous state is dropped without holding the locks, which closes e.g. the file dump
+    let _previous = {
+        let mut hs = TUI_LOGGER.hot_select.lock();
+        hs.hashtable.clear();
+        hs.modules.clear();
+        hs.env_directives.clear();
+        hs.default = LevelFilter::Info;
+        let mut tli = TUI_LOGGER.inner.lock();
+        let mut inner = new_inner();
+        // Let the widgets detect the change of the targets
+        inner.targets.generation = tli.targets.generation + 1;
+        mem::replace(&mut *tli, inner)
+    };
+    TUI_LOGGER.update_hot();
+}
+
+fn spawn_mover() -> Result<thread::JoinHandle<()>, TuiLoggerError> {
+    thread::Builder::new()
+        .name("tui-logger::move_events".into())
+        .spawn(|| loop {
+            // Sleep until the first event arrives, then wait for more events to move them
+            // in one batch. If the hot buffer fills up to the wake fraction, the wait is cut short.
+            TUI_LOGGER.hot_log.mover_idle.store(true, Ordering::Relaxed);
+            fence(Ordering::SeqCst);
+            if TUI_LOGGER.hot_is_empty() && !TUI_LOGGER.hot_log.mover_stop.load(Ordering::SeqCst) {
+                thread::park();
+            }
+            TUI_LOGGER
+                .hot_log
+                .mover_idle
+                .store(false, Ordering::Relaxed);
+            if TUI_LOGGER.hot_log.mover_stop.load(Ordering::SeqCst) {
+                break;
+            }
+            let latency = TUI_LOGGER.inner.lock().move_latency;
+            thread::park_timeout(latency);
+            TUI_LOGGER.move_events_resilient();
+        })
+        .map_err(TuiLoggerError::ThreadError)
+}
+
 #[cfg(feature = "slog-support")]
 #[cfg_attr(docsrs, doc(cfg(feature = "slog-support")))]
 pub fn slog_drain() -> TuiSlogDrain {
-    TuiSlogDrain
+    TuiSlogDrain::default()
 }
 
 #[cfg(feature = "tracing-support")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tracing-support")))]
 pub fn tracing_subscriber_layer() -> TuiTracingSubscriberLayer {
-    TuiTracingSubscriberLayer
+    TuiTracingSubscriberLayer::default()
+}
+
+/// Time of logger initialization, which is the reference for relative timestamps
+fn start_time() -> Timestamp {
+    *TUI_LOGGER.start_time.lock()
+}
+
+/// Set the timezone, in which the widgets and the file dump show the timestamps.
+/// Both can override this setting.
+///
+/// Default is the local timezone
+pub fn set_timezone(timezone: TuiLoggerTimezone) {
+    *TUI_LOGGER.timezone.lock() = timezone;
+}
+
+fn timezone() -> TuiLoggerTimezone {
+    *TUI_LOGGER.timezone.lock()
+}
+
+#[cfg(feature = "kv")]
+fn collect_key_values(record: &Record) -> Vec<(String, String)> {
+    struct Collector(Vec<(String, String)>);
+    impl<'kvs> log::kv::VisitSource<'kvs> for Collector {
+        fn visit_pair(
+            &mut self,
+            key: log::kv::Key<'kvs>,
+            value: log::kv::Value<'kvs>,
+        ) -> Result<(), log::kv::Error> {
+            self.0.push((key.to_string(), value.to_string()));
+            Ok(())
+        }
+    }
+    let mut collector = Collector(vec![]);
+    // The collector never fails
+    let _ = record.key_values().visit(&mut collector);
+    collector.0
+}
+
+/// Format a duration as e.g. 12.3ms, 1.234s or 2m05s
+fn format_duration(micros: i64) -> String {
+    let micros = micros.max(0);
+    if micros < 1_000_000 {
+        format!("{:.1}ms", micros as f64 / 1000.0)
+    } else if micros < 60_000_000 {
+        format!("{:.3}s", micros as f64 / 1_000_000.0)
+    } else {
+        let secs = micros / 1_000_000;
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    }
+}
+
+/// Format the process id or ? if unknown
+fn format_pid(pid: Option<u32>) -> String {
+    pid.map(|pid| pid.to_string())
+        .unwrap_or_else(|| "?".to_string())
+}
+
+/// Format the time elapsed since start as e.g. 00:03:12.456
+fn format_elapsed(elapsed: timestamp::TimeDelta) -> String {
+    let millis = elapsed.num_milliseconds().max(0);
+    let secs = millis / 1000;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        secs / 3600,
+        (secs / 60) % 60,
+        secs % 60,
+        millis % 1000
+    )
+}
+
+/// Replace the clock, which provides the timestamps of the captured events.
+/// This should be called before init_logger(), which takes the start time for
+/// relative timestamps from the clock.
+pub fn set_clock(clock: Box<dyn Clock>) {
+    *TUI_LOGGER.hot_log.clock.write() = clock;
+}
+
+/// Log a message without constructing a `log::Record`, e.g. for scripting layers.
+/// The log levels are checked as for events of the log crate.
+pub fn log_str(level: Level, target: &str, message: &str) {
+    TUI_LOGGER.log_str(level, target, message);
+}
+
+/// Inject a record, e.g. imported from another system or replayed from a file.
+/// The record is processed like a captured event, but the log levels are not checked.
+/// The sequence number is assigned by move_events().
+pub fn push_record(record: ExtLogRecord) {
+    TUI_LOGGER.push_hot(record);
 }
 
 /// Set the depth of the hot buffer in order to avoid message loss.
@@ -536,21 +1506,366 @@ pub fn set_hot_buffer_depth(depth: usize) {
     TUI_LOGGER.inner.lock().hot_depth = depth;
 }
 
+/// Set the maximum delay, by which the mover thread moves new events from the hot buffer
+/// into the main buffer. Events arriving within this time are moved in one batch, unless the
+/// hot buffer gets half full. Without events, the mover thread sleeps without any wakeups.
+///
+/// Default is 10 ms.
+pub fn set_max_move_latency(latency: Duration) {
+    TUI_LOGGER.inner.lock().move_latency = latency;
+}
+
+/// Split the hot buffer into the given number of shards, each with the depth of the hot buffer.
+/// The logging threads are assigned to the shards in turn, which reduces the contention, if
+/// many threads log concurrently. move_events() merges the shards by the timestamps.
+/// This is effective only after a call to move_events()
+pub fn set_hot_buffer_shards(shards: usize) {
+    TUI_LOGGER.inner.lock().hot_shards = shards;
+}
+
 /// Set the depth of the circular buffer in order to avoid message loss.
 /// This will delete all existing messages in the circular buffer.
 pub fn set_buffer_depth(depth: usize) {
-    TUI_LOGGER.inner.lock().events = CircularBuffer::new(depth);
+    TUI_LOGGER.inner.lock().events.set_depth(depth);
+}
+
+/// Use a separate buffer for each level and set the depth of the level's buffer,
+/// e.g. to keep more errors than trace events. 0 drops the events of the level.
+/// The events of all levels are displayed in their order.
+///
+/// On the first call all events are removed and the other levels get the depth of the
+/// main buffer. set_buffer_depth() switches back to a single buffer.
+pub fn set_buffer_depth_for_level(level: Level, depth: usize) {
+    TUI_LOGGER.inner.lock().events.set_level_depth(level, depth);
+}
+
+/// Keep the events in a buffer of the given depth per target, so a single chatty target
+/// cannot evict the history of all the others. If the total number of events exceeds `total`,
+/// the oldest event of the target with the most events is removed.
+/// All events are removed. set_buffer_depth() switches back to a single buffer.
+pub fn set_buffer_depth_per_target(depth: usize, total: usize) {
+    TUI_LOGGER
+        .inner
+        .lock()
+        .events
+        .set_target_depth(depth, total);
+}
+
+/// Limit the buffer by the approximate memory usage of the events in bytes instead of
+/// their number, as the sizes of the messages vary. The oldest events are removed first.
+/// The events in the buffer are kept as far as they fit. set_buffer_depth() switches back.
+pub fn set_buffer_memory_limit(bytes: usize) {
+    TUI_LOGGER.inner.lock().events.set_memory_limit(bytes);
+}
+
+/// Write the events evicted from the buffer to the file instead of dropping them,
+/// giving effectively unlimited history. In page mode, the widget loads them on scrolling
+/// back beyond the oldest event in the buffer, and frees them on returning to the newest.
+/// Key-value pairs, spans and backtraces are not kept. The file is removed on replacing it,
+/// on `set_spill_file(None)` and by [`shutdown`], which should be called on exit.
+/// This does not apply to the buffers per level or per target.
+pub fn set_spill_file(path: Option<&str>) -> std::io::Result<()> {
+    TUI_LOGGER
+        .inner
+        .lock()
+        .events
+        .set_spill_file(path.map(std::path::Path::new))
+}
+
+/// Keep the events evicted from the buffer in memory as gzip compressed blocks of
+/// `block_size` events instead of dropping them. The oldest blocks are dropped, if the
+/// compressed blocks exceed `max_bytes`. In page mode, the widget decompresses the blocks
+/// around the viewed event on scrolling back. Like for [`set_spill_file`], key-value pairs,
+/// spans and backtraces are not kept. This replaces a spill file, and a `block_size` of 0
+/// stops keeping the evicted events.
+/// This does not apply to the buffers per level or per target.
+#[cfg(feature = "gzip")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+pub fn set_compressed_history(block_size: usize, max_bytes: usize) {
+    TUI_LOGGER
+        .inner
+        .lock()
+        .events
+        .set_compressed(block_size, max_bytes);
+}
+
+/// Keep up to `depth` error and warning events, which have been evicted from the buffer,
+/// so these are not the first to be lost under a flood of trace events. 0 disables this.
+/// The retained events are displayed before the events in the buffer.
+/// This does not apply to the buffers per level or per target.
+pub fn set_severe_retention(depth: usize) {
+    TUI_LOGGER.inner.lock().events.set_retained_depth(depth);
+}
+
+/// Remove all events from the main buffer and drop the not yet moved events.
+/// The total number of events and the lost events are reset, too.
+pub fn clear_events() {
+    for shard in TUI_LOGGER.hot_log.shards.read().iter() {
+        while shard.events.pop().is_some() {}
+    }
+    TUI_LOGGER.hot_log.lost.store(0, Ordering::Relaxed);
+    let mut tli = TUI_LOGGER.inner.lock();
+    tli.events.clear();
+    for domain in tli.domains.iter_mut() {
+        domain.events.clear();
+    }
+    tli.total_events = 0;
+    tli.lost_events = 0;
+    tli.hot_high_water = 0;
+    tli.level_counts = [0; 5];
+}
+
+/// Statistics of the logger, e.g. to display its health or to assert on it in tests.
+/// See [`stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TuiLoggerStats {
+    /// Number of received events, including the lost ones
+    pub total_events: usize,
+    /// Number of events in the main buffer and the buffers of the log domains
+    pub buffered_events: usize,
+    /// Number of events lost by overflow of the hot buffer
+    pub lost_events: usize,
+    /// The most events, which have been waiting in the hot buffer to be moved
+    pub hot_buffer_high_water: usize,
+    level_counts: [usize; 5],
+}
+impl TuiLoggerStats {
+    /// Number of stored events of the level. The events dropped by the capture filter,
+    /// the sampling or as duplicates are not counted.
+    pub fn level_count(&self, level: Level) -> usize {
+        self.level_counts[level as usize - 1]
+    }
+}
+
+/// Return the statistics of the logger since the start or the last [`clear_events`].
+/// The pending events are moved before.
+pub fn stats() -> TuiLoggerStats {
+    TUI_LOGGER.move_events();
+    let tli = TUI_LOGGER.inner.lock();
+    TuiLoggerStats {
+        total_events: tli.total_events,
+        buffered_events: tli.events.len()
+            + tli
+                .domains
+                .iter()
+                .map(|domain| domain.events.len())
+                .sum::<usize>(),
+        lost_events: tli.lost_events,
+        hot_buffer_high_water: tli.hot_high_water,
+        level_counts: tli.level_counts,
+    }
+}
+
+/// Remove all events of the given target from the main buffer and the buffers of the
+/// log domains. The remaining events are kept in order and the freed space is available
+/// for new events.
+pub fn clear_events_for_target(target: &str) {
+    // Events still in the hot buffer have to be purged, too
+    TUI_LOGGER.move_events();
+    let mut tli = TUI_LOGGER.inner.lock();
+    for store in tli.all_stores() {
+        let target = target.to_string();
+        store.retain(move |evt| evt.target != target);
+    }
+}
+
+/// Remove ANSI escape sequences from the messages, before the events are stored.
+/// This applies to the widgets and the file dump.
+/// This is effective for events moved by move_events() after this call.
+pub fn set_strip_ansi(enabled: bool) {
+    TUI_LOGGER.inner.lock().strip_ansi = enabled;
+}
+
+/// Drop the events, for which the predicate returns false, before they are stored
+/// in the buffer and written to the sinks, e.g. known noise of a chatty library:
+/// ```
+/// tui_logger::set_capture_filter(|evt| !evt.msg.contains("heartbeat"));
+/// ```
+/// The predicate is called with the logger locked, so it must not log itself.
+pub fn set_capture_filter(filter: impl Fn(&ExtLogRecord) -> bool + Send + 'static) {
+    TUI_LOGGER.inner.lock().capture_filter = Some(Box::new(filter));
+}
+
+/// Drop the events, which contain any of the substrings in the message
+pub fn set_capture_filter_substrings(substrings: &[&str]) {
+    let substrings: Vec<String> = substrings.iter().map(|s| s.to_string()).collect();
+    set_capture_filter(move |evt| !substrings.iter().any(|s| evt.msg.contains(s.as_str())));
+}
+
+/// Capture all events again
+pub fn clear_capture_filter() {
+    TUI_LOGGER.inner.lock().capture_filter = None;
+}
+
+/// Keep only 1 of every `every` debug and trace events of the target, so high-frequency
+/// tracing can stay enabled with bounded cost. Errors, warnings and infos are always kept.
+/// A value of 0 or 1 keeps all events again.
+pub fn set_sampling_for_target(target: &str, every: usize) {
+    let mut tli = TUI_LOGGER.inner.lock();
+    if every > 1 {
+        tli.sampling.insert(target.to_string(), (every, 0));
+    } else {
+        tli.sampling.remove(target);
+    }
+}
+
+/// Fold consecutive identical messages of the same target and level into the first one,
+/// followed by an event "last message repeated N times" like syslog does.
+/// This event is stored, when a different event arrives or at the latest at the end of
+/// the batch moved from the hot buffer. So a long run may be summarized by several events.
+pub fn set_collapse_duplicates(enabled: bool) {
+    let mut tli = TUI_LOGGER.inner.lock();
+    tli.collapse_duplicates = enabled;
+    tli.last_event = None;
+    tli.duplicates = 0;
+    tli.last_duplicate = None;
 }
 
 /// Define filename and log formmating options for file dumping.
+/// This replaces the file defined by a previous call. Further files can be added by add_sink().
 pub fn set_log_file(file_options: TuiLoggerFile) {
-    TUI_LOGGER.inner.lock().dump = Some(file_options);
+    let id = add_sink(file_options);
+    let mut tli = TUI_LOGGER.inner.lock();
+    if let Some(previous) = tli.log_file.replace(id) {
+        tli.sinks.retain(|(sink_id, _)| *sink_id != previous);
+    }
 }
 
-/// Set default levelfilter for unknown targets of the logger
+/// Add an output for the captured events, e.g. a [`TuiLoggerFile`], a [`TuiLoggerSyslog`]
+/// or a [`TuiLoggerNetSink`]. All sinks receive the events in order of registration.
+pub fn add_sink(sink: impl LogSink + 'static) -> SinkId {
+    let mut tli = TUI_LOGGER.inner.lock();
+    let id = SinkId(tli.next_sink_id);
+    tli.next_sink_id += 1;
+    tli.sinks.push((id, Box::new(sink)));
+    id
+}
+
+/// Call the hook for every captured event, e.g. to count metrics, raise alerts or forward
+/// the events. The hook is a sink and is removed by [`remove_sink`].
+/// It is called with the logger locked, so it must not call functions of tui-logger.
+pub fn add_event_hook(hook: Box<dyn Fn(&ExtLogRecord) + Send + Sync>) -> SinkId {
+    add_sink(EventHook(hook))
+}
+
+/// Return a receiver of the captured events, e.g. for an application task showing a popup
+/// on errors. It receives the events in the same order as the widgets, from this call on.
+///
+/// If the receiver lags behind by more than 1000 events, the newer events are dropped for it.
+/// After the receiver is dropped, the events are no longer sent.
+pub fn event_receiver() -> Receiver<Arc<ExtLogRecord>> {
+    let (sender, receiver) = mpsc::sync_channel(EVENT_CHANNEL_DEPTH);
+    add_sink(ChannelSink(Some(sender)));
+    receiver
+}
+
+/// Remove the sink. Returns false, if the sink is not registered.
+pub fn remove_sink(id: SinkId) -> bool {
+    let mut tli = TUI_LOGGER.inner.lock();
+    let len = tli.sinks.len();
+    tli.sinks.retain(|(sink_id, _)| *sink_id != id);
+    if tli.log_file == Some(id) {
+        tli.log_file = None;
+    }
+    tli.sinks.len() != len
+}
+
+/// Write the pending events to the sinks and flush them, e.g. before exiting
+/// or in a panic hook. `log::logger().flush()` calls this, too.
+pub fn flush_sinks() {
+    TUI_LOGGER.move_events();
+    let mut tli = TUI_LOGGER.inner.lock();
+    for (_, sink) in tli.sinks.iter_mut() {
+        sink.flush();
+    }
+    for domain in tli.domains.iter_mut() {
+        if let Some(sink) = domain.sink.as_mut() {
+            sink.flush();
+        }
+    }
+}
+
+/// Stop the processing of the events, e.g. when the TUI exits: The mover thread is stopped
+/// and joined, the pending events are written to the sinks, and all sinks are flushed and
+/// removed, which closes the file dump. Remote viewers are disconnected,
+/// and the spill file is removed together with the events spilled to it.
+///
+/// The logger stays registered, but the events are no longer processed, until
+/// [`init_drain`] is called again.
+pub fn shutdown() {
+    let mover_thread = TUI_LOGGER.hot_log.mover_thread.lock().take();
+    if let Some(join_handle) = mover_thread {
+        TUI_LOGGER.hot_log.mover_stop.store(true, Ordering::SeqCst);
+        join_handle.thread().unpark();
+        let _ = join_handle.join();
+        TUI_LOGGER.hot_log.mover_stop.store(false, Ordering::SeqCst);
+    }
+    flush_sinks();
+    TUI_LOGGER
+        .hot_log
+        .manual_move
+        .store(false, Ordering::Relaxed);
+    // The sinks are dropped without holding the lock
+    let _sinks = {
+        let mut tli = TUI_LOGGER.inner.lock();
+        tli.log_file = None;
+        #[cfg(feature = "remote")]
+        tli.remote_clients.clear();
+        let domain_sinks: Vec<Box<dyn LogSink>> = tli
+            .domains
+            .iter_mut()
+            .filter_map(|domain| domain.sink.take())
+            .collect();
+        (
+            mem::take(&mut tli.sinks),
+            domain_sinks,
+            tli.events.take_spill_file(),
+        )
+    };
+}
+
+/// Set default levelfilter for unknown targets of the logger.
+/// Already seen targets without own levelfilter follow the new default, too.
 pub fn set_default_level(levelfilter: LevelFilter) {
-    TUI_LOGGER.hot_select.lock().default = levelfilter;
-    TUI_LOGGER.inner.lock().default = levelfilter;
+    let mut hs = TUI_LOGGER.hot_select.lock();
+    hs.default = levelfilter;
+    let mut tli = TUI_LOGGER.inner.lock();
+    tli.default = levelfilter;
+    update_inherited_levels(&hs, &mut tli);
+}
+
+/// Only targets with own levelfilter are in the hashtable. The others have been
+/// recorded with the previous default or env filter by move_events(). Updating them increments
+/// the generation of the LevelConfig, so the widgets pick up the change.
+fn update_inherited_levels(hs: &HotSelect, tli: &mut TuiLoggerInner) {
+    let targets: Vec<String> = tli
+        .targets
+        .keys()
+        .filter(|target| !hs.hashtable.contains_key(&fxhash::hash64(target.as_str())))
+        .cloned()
+        .collect();
+    for target in targets {
+        let levelfilter = tli.inherited_level(&target);
+        tli.targets.set(&target, levelfilter);
+    }
+}
+
+/// Replace the directives of the env filter. Returns the previous ones.
+fn set_env_directives(directives: Vec<(String, LevelFilter)>) -> Vec<(String, LevelFilter)> {
+    let mut hs = TUI_LOGGER.hot_select.lock();
+    hs.env_directives = directives.clone();
+    let mut tli = TUI_LOGGER.inner.lock();
+    let previous = mem::replace(&mut tli.env_filter_targets, directives);
+    update_inherited_levels(&hs, &mut tli);
+    previous
+}
+
+/// Set levelfilter for a module path, e.g. `my_crate::db`, in the logger.
+/// It takes precedence over the levelfilter of the target,
+/// e.g. for libraries, which use arbitrary strings as target.
+pub fn set_level_for_module(module_path: &str, levelfilter: LevelFilter) {
+    let h = fxhash::hash64(module_path);
+    TUI_LOGGER.hot_select.lock().modules.insert(h, levelfilter);
 }
 
 /// Set levelfilter for a specific target in the logger
@@ -561,50 +1876,282 @@ pub fn set_level_for_target(target: &str, levelfilter: LevelFilter) {
     hs.hashtable.insert(h, levelfilter);
 }
 
+/// All targets seen so far sorted by name, e.g. for an own settings UI or for persisting them.
+/// Each target comes with its capture level and its own levelfilter as set by
+/// [`set_level_for_target`], which is None, if the target follows the default level.
+pub fn targets() -> Vec<(String, LevelFilter, Option<LevelFilter>)> {
+    let hs = TUI_LOGGER.hot_select.lock();
+    let tli = TUI_LOGGER.inner.lock();
+    let mut targets: Vec<(String, LevelFilter, Option<LevelFilter>)> = tli
+        .targets
+        .iter()
+        .map(|(target, levelfilter)| {
+            let own = hs.hashtable.get(&fxhash::hash64(target.as_str())).copied();
+            (target.clone(), *levelfilter, own)
+        })
+        .collect();
+    targets.sort();
+    targets
+}
+
+/// Forget the target together with its own levelfilter, e.g. of a completed subsystem.
+/// The target is removed from the target selectors, too. If it logs again,
+/// it is recorded with the default level.
+pub fn remove_target(target: &str) {
+    let mut hs = TUI_LOGGER.hot_select.lock();
+    hs.hashtable.remove(&fxhash::hash64(target));
+    let mut tli = TUI_LOGGER.inner.lock();
+    if tli.targets.remove(target).is_some() {
+        tli.removed_targets.insert(target.to_string());
+    }
+}
+
+/// Keep only the targets, for which the predicate returns true, see [`remove_target`].
+pub fn retain_targets<F>(mut f: F)
+where
+    F: FnMut(&str, LevelFilter) -> bool,
+{
+    let mut hs = TUI_LOGGER.hot_select.lock();
+    let mut tli = TUI_LOGGER.inner.lock();
+    let mut removed = vec![];
+    tli.targets.retain(|target, levelfilter| {
+        let keep = f(target, levelfilter);
+        if !keep {
+            hs.hashtable.remove(&fxhash::hash64(target));
+            removed.push(target.to_string());
+        }
+        keep
+    });
+    tli.removed_targets.extend(removed);
+}
+
+/// Forget all targets together with their own levelfilters, see [`remove_target`].
+pub fn clear_targets() {
+    let mut hs = TUI_LOGGER.hot_select.lock();
+    hs.hashtable.clear();
+    let mut tli = TUI_LOGGER.inner.lock();
+    let removed: Vec<String> = tli.targets.keys().cloned().collect();
+    tli.targets.clear();
+    tli.removed_targets.extend(removed);
+}
+
+/// Let the target follow the default level again
+fn reset_level_for_target(target: &str) {
+    let h = fxhash::hash64(&target);
+    {
+        let mut tli = TUI_LOGGER.inner.lock();
+        let levelfilter = tli.inherited_level(target);
+        tli.targets.set(target, levelfilter);
+    }
+    TUI_LOGGER.hot_select.lock().hashtable.remove(&h);
+}
+
+/// Set the levelfilter of all targets in the logger back to the default level.
+/// The directives of the env filter are dropped, too. Returns the default level.
+fn reset_level_for_all_targets() -> LevelFilter {
+    let mut hs = TUI_LOGGER.hot_select.lock();
+    hs.hashtable.clear();
+    hs.env_directives.clear();
+    let mut tli = TUI_LOGGER.inner.lock();
+    tli.env_filter_targets.clear();
+    let default = tli.default;
+    let targets: Vec<String> = tli.targets.keys().cloned().collect();
+    for target in targets {
+        tli.targets.set(&target, default);
+    }
+    default
+}
+
 impl TuiLogger {
     fn raw_log(&self, record: &Record) {
-        let log_entry = ExtLogRecord {
-            timestamp: chrono::Local::now(),
+        self.raw_log_with(record, |_| {});
+    }
+    /// Log the record, after completing the ExtLogRecord by the given closure.
+    fn raw_log_with(&self, record: &Record, complete: impl FnOnce(&mut ExtLogRecord)) {
+        let target = record.target().to_string();
+        let module_path = record.module_path().map(str::to_string);
+        let file = record.file().unwrap_or("?").to_string();
+        let msg = format!("{}", record.args());
+        #[cfg(feature = "kv")]
+        let key_values = collect_key_values(record);
+        #[cfg(not(feature = "kv"))]
+        let key_values = vec![];
+        #[cfg(feature = "backtrace-support")]
+        let backtrace = (record.level() == Level::Error)
+            .then(|| std::backtrace::Backtrace::force_capture().to_string());
+        #[cfg(not(feature = "backtrace-support"))]
+        let backtrace = None;
+        let mut log_entry = ExtLogRecord {
+            seq: 0,
+            timestamp: self.now(),
+            pid: Some(std::process::id()),
             level: record.level(),
-            target: record.target().to_string(),
-            file: record.file().unwrap_or("?").to_string(),
+            target,
+            module_path,
+            file,
             line: record.line().unwrap_or(0),
-            msg: format!("{}", record.args()),
+            msg,
+            key_values,
+            spans: vec![],
+            span_duration: None,
+            backtrace,
         };
-        let mut events_lock = self.hot_log.lock();
-        events_lock.events.push(log_entry);
-        let need_signal =
-            (events_lock.events.total_elements() % (events_lock.events.capacity() / 2)) == 0;
+        complete(&mut log_entry);
+        self.push_hot(log_entry);
+    }
+    fn level_enabled(&self, level: Level, target: &str) -> bool {
+        self.module_level_enabled(level, target, None)
+    }
+    /// Like level_enabled(), but a levelfilter set for the module path takes precedence
+    fn module_level_enabled(&self, level: Level, target: &str, module_path: Option<&str>) -> bool {
+        let hs = self.hot_select.lock();
+        if let Some(module_path) = module_path.filter(|_| !hs.modules.is_empty()) {
+            if let Some(&levelfilter) = hs.modules.get(&fxhash::hash64(module_path)) {
+                return level <= levelfilter;
+            }
+        }
+        let h = fxhash::hash64(target);
+        if let Some(&levelfilter) = hs.hashtable.get(&h) {
+            level <= levelfilter
+        } else if let Some(levelfilter) = levels::directive_level(&hs.env_directives, target) {
+            level <= levelfilter
+        } else {
+            level <= hs.default
+        }
+    }
+    fn log_str(&self, level: Level, target: &str, message: &str) {
+        if !self.level_enabled(level, target) {
+            return;
+        }
+        let log_entry =
+            ExtLogRecord::new(self.now(), level, target, message).pid(std::process::id());
+        self.push_hot(log_entry);
+    }
+    /// Called by the widgets before rendering
+    fn move_events_if_manual(&self) {
+        if self.hot_log.manual_move.load(Ordering::Relaxed) {
+            self.move_events();
+        }
+    }
+    /// Like move_events(), but a panic, e.g. of a sink, does not end the mover thread.
+    /// Instead an error event is stored, which bypasses the sinks, so a failing sink
+    /// does not panic again on the report.
+    fn move_events_resilient(&self) {
+        let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| self.move_events())) else {
+            return;
+        };
+        let reason = payload
+            .downcast_ref::<&str>()
+            .map(|reason| reason.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown reason".to_string());
+        let mut log_entry = ExtLogRecord::new(
+            self.now(),
+            Level::Error,
+            "TuiLogger",
+            &format!(
+                "Moving the events panicked: {}. Events of this batch may be lost.",
+                reason
+            ),
+        )
+        .pid(std::process::id());
+        let mut tli = self.inner.lock();
+        log_entry.seq = tli.next_seq;
+        tli.next_seq += 1;
+        tli.events.push(log_entry);
+    }
+    fn hot_is_empty(&self) -> bool {
+        self.hot_log
+            .shards
+            .read()
+            .iter()
+            .all(|shard| shard.events.is_empty())
+            && self.hot_log.lost.load(Ordering::Relaxed) == 0
+    }
+    /// Apply the configuration of the hot buffer, if it has been changed
+    fn update_hot(&self) {
+        let (depth, shards, wake_at) = {
+            let tli = self.inner.lock();
+            let depth = tli.hot_depth.max(1);
+            let wake_at = ((depth as f32 * tli.wake_fraction) as usize).clamp(1, depth);
+            (depth, tli.hot_shards.max(1), wake_at)
+        };
+        let hot_shards = self.hot_log.shards.read();
+        if hot_shards.len() != shards
+            || hot_shards[0].events.capacity() != depth
+            || hot_shards[0].wake_at != wake_at
+        {
+            drop(hot_shards);
+            self.resize_hot(depth, shards, wake_at);
+        }
+    }
+    /// Replace the shards of the hot buffer. The not yet moved events are kept, as far as
+    /// these fit into the first shard.
+    fn resize_hot(&self, depth: usize, shards: usize, wake_at: usize) {
+        let mut hot_shards = self.hot_log.shards.write();
+        let mut pending = vec![];
+        for shard in hot_shards.iter() {
+            while let Some(log_entry) = shard.events.pop() {
+                pending.push(log_entry);
+            }
+        }
+        pending.sort_by_key(|log_entry| log_entry.timestamp);
+        *hot_shards = (0..shards).map(|_| HotShard::new(depth, wake_at)).collect();
+        for log_entry in pending {
+            if hot_shards[0].events.force_push(log_entry).is_some() {
+                self.hot_log.lost.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+    /// Timestamp from the logger's clock
+    fn now(&self) -> Timestamp {
+        self.hot_log.clock.read().now()
+    }
+    /// Push the record into the hot buffer and wake up the mover thread, if needed.
+    /// If the hot buffer is full, the oldest event is lost.
+    fn push_hot(&self, log_entry: ExtLogRecord) {
+        let need_signal = {
+            let shards = self.hot_log.shards.read();
+            let shard = &shards[HOT_SHARD.with(|idx| *idx) % shards.len()];
+            if shard.events.force_push(log_entry).is_some() {
+                self.hot_log.lost.fetch_add(1, Ordering::Relaxed);
+            }
+            let pushed = shard.pushed.fetch_add(1, Ordering::Relaxed) + 1;
+            pushed.is_multiple_of(shard.wake_at)
+        };
+        // Pairs with the fence of the idle mover thread, so it either sees the event or is woken
+        fence(Ordering::SeqCst);
+        let need_signal = need_signal
+            || (self.hot_log.mover_idle.load(Ordering::Relaxed)
+                && self.hot_log.mover_idle.swap(false, Ordering::Relaxed));
         if need_signal {
-            events_lock
-                .mover_thread
-                .as_ref()
-                .map(|jh| thread::Thread::unpark(jh.thread()));
+            if let Some(jh) = self.hot_log.mover_thread.lock().as_ref() {
+                thread::Thread::unpark(jh.thread());
+            }
         }
     }
 }
 
 impl Log for TuiLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        let h = fxhash::hash64(metadata.target());
-        let hs = self.hot_select.lock();
-        if let Some(&levelfilter) = hs.hashtable.get(&h) {
-            metadata.level() <= levelfilter
-        } else {
-            metadata.level() <= hs.default
-        }
+        self.level_enabled(metadata.level(), metadata.target())
     }
 
     fn log(&self, record: &Record) {
-        if self.enabled(record.metadata()) {
+        if self.module_level_enabled(record.level(), record.target(), record.module_path()) {
             self.raw_log(record)
         }
     }
 
-    fn flush(&self) {}
+    fn flush(&self) {
+        flush_sinks()
+    }
 }
 
 /// A simple `Drain` to log any event directly.
+///
+/// It implements `log::Log` without any filtering, so it can be chained
+/// into other loggers like `fern::Dispatch`.
 #[derive(Default)]
 pub struct Drain;
 
@@ -619,6 +2166,20 @@ impl Drain {
     }
 }
 
+impl Log for Drain {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        TUI_LOGGER.raw_log(record)
+    }
+
+    fn flush(&self) {
+        flush_sinks()
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum TuiWidgetEvent {
     SpaceKey,
@@ -633,6 +2194,42 @@ pub enum TuiWidgetEvent {
     PrevPageKey,
     NextPageKey,
     EscapeKey,
+    TopKey,
+    BottomKey,
+    PauseKey,
+    ClearKey,
+    AllLeftKey,
+    AllRightKey,
+    ResetKey,
+    ToggleWrapKey,
+    /// Export the visible events as CSV to the path set by TuiWidgetState::set_export_path()
+    ExportKey,
+    /// Apply RUST_LOG again by reload_env_filter(). Not bound to a key by default.
+    ReloadFilterKey,
+    /// Set the display level of the selected target
+    SetDisplayLevel(LevelFilter),
+    /// Set the display level of all targets
+    SetAllDisplayLevel(LevelFilter),
+}
+
+/// Result of handling a TuiWidgetEvent by TuiWidgetState::handle_event()
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum TuiWidgetEventResult {
+    /// The event has no meaning in the widget's current state,
+    /// e.g. ESCAPE outside of page mode. The application may use it.
+    NotHandled,
+    /// The event has been handled, but nothing has changed.
+    Handled,
+    /// The event has changed the widget's state and a redraw is needed.
+    Redraw,
+}
+impl TuiWidgetEventResult {
+    pub fn is_handled(&self) -> bool {
+        *self != TuiWidgetEventResult::NotHandled
+    }
+    pub fn needs_redraw(&self) -> bool {
+        *self == TuiWidgetEventResult::Redraw
+    }
 }
 
 /// This is the definition for the TuiLoggerTargetWidget,
@@ -646,7 +2243,6 @@ pub struct TuiLoggerTargetWidget<'b> {
     style_off: Option<Style>,
     highlight_style: Style,
     state: Arc<Mutex<TuiWidgetInnerState>>,
-    targets: Vec<String>,
 }
 impl<'b> Default for TuiLoggerTargetWidget<'b> {
     fn default() -> TuiLoggerTargetWidget<'b> {
@@ -659,7 +2255,6 @@ impl<'b> Default for TuiLoggerTargetWidget<'b> {
             style_show: Style::default().add_modifier(Modifier::REVERSED),
             highlight_style: Style::default().add_modifier(Modifier::REVERSED),
             state: Arc::new(Mutex::new(TuiWidgetInnerState::new())),
-            targets: vec![],
         }
     }
 }
@@ -728,12 +2323,24 @@ impl<'b> TuiLoggerTargetWidget<'b> {
     }
 }
 impl<'b> Widget for TuiLoggerTargetWidget<'b> {
-    fn render(mut self, area: Rect, buf: &mut Buffer) {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.draw(area, buf);
+    }
+}
+#[cfg(feature = "widget-ref")]
+#[cfg_attr(docsrs, doc(cfg(feature = "widget-ref")))]
+impl<'b> WidgetRef for TuiLoggerTargetWidget<'b> {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        self.draw(area, buf);
+    }
+}
+impl<'b> TuiLoggerTargetWidget<'b> {
+    fn draw(&self, area: Rect, buf: &mut Buffer) {
         buf.set_style(area, self.style);
-        let list_area = match self.block.take() {
+        let list_area = match self.block.as_ref() {
             Some(b) => {
                 let inner_area = b.inner(area);
-                b.render(area, buf);
+                b.clone().render(area, buf);
                 inner_area
             }
             None => area,
@@ -753,25 +2360,36 @@ impl<'b> Widget for TuiLoggerTargetWidget<'b> {
             let hide_off = state.hide_off;
             let offset = state.offset;
             let focus_selected = state.focus_selected;
+            let mut targets_list = vec![];
             {
                 let targets = &mut state.config;
                 targets.merge(hot_targets);
-                self.targets.clear();
                 for (t, levelfilter) in targets.iter() {
                     if hide_off && levelfilter == &LevelFilter::Off {
                         continue;
                     }
-                    self.targets.push(t.clone());
+                    // Targets removed from the logger are not listed anymore,
+                    // but targets only known to this widget are
+                    if inner.removed_targets.contains(t) && hot_targets.get(t).is_none() {
+                        continue;
+                    }
+                    targets_list.push(t.clone());
+                }
+                targets_list.sort();
+            }
+            state.nr_items = targets_list.len();
+            if let Some(pending) = state.opt_pending_target.as_ref() {
+                if let Ok(pos) = targets_list.binary_search(pending) {
+                    state.selected = pos;
+                    state.opt_pending_target = None;
                 }
-                self.targets.sort();
             }
-            state.nr_items = self.targets.len();
             if state.selected >= state.nr_items {
                 state.selected = state.nr_items.max(1) - 1;
             }
             if state.selected < state.nr_items {
-                state.opt_selected_target = Some(self.targets[state.selected].clone());
-                let t = &self.targets[state.selected];
+                state.opt_selected_target = Some(targets_list[state.selected].clone());
+                let t = &targets_list[state.selected];
                 let (more, less) = if let Some(levelfilter) = state.config.get(t) {
                     advance_levelfilter(levelfilter)
                 } else {
@@ -787,16 +2405,16 @@ impl<'b> Widget for TuiLoggerTargetWidget<'b> {
                 state.opt_selected_recording_less = less;
                 state.opt_selected_recording_more = more;
             }
-            let list_height = (list_area.height as usize).min(self.targets.len());
-            let offset = if list_height > self.targets.len() {
+            let list_height = (list_area.height as usize).min(targets_list.len());
+            let offset = if list_height > targets_list.len() {
                 0
             } else if state.selected < state.nr_items {
                 let sel = state.selected;
                 if sel >= offset + list_height {
                     // selected is below visible list range => make it the bottom
                     sel - list_height + 1
-                } else if sel.min(offset) + list_height > self.targets.len() {
-                    self.targets.len() - list_height
+                } else if sel.min(offset) + list_height > targets_list.len() {
+                    targets_list.len() - list_height
                 } else {
                     sel.min(offset)
                 }
@@ -808,7 +2426,7 @@ impl<'b> Widget for TuiLoggerTargetWidget<'b> {
             let targets = &(&state.config);
             let default_level = inner.default;
             for i in 0..list_height {
-                let t = &self.targets[i + offset];
+                let t = &targets_list[i + offset];
                 // Comment in relation to issue #69:
                 // Widgets maintain their own list of level filters per target.
                 // These lists are not forwarded to the TUI_LOGGER, but kept widget private.
@@ -865,6 +2483,14 @@ impl<'b> Widget for TuiLoggerTargetWidget<'b> {
     }
 }
 
+impl<'b> StatefulWidget for TuiLoggerTargetWidget<'b> {
+    type State = TuiWidgetState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut TuiWidgetState) {
+        Widget::render(self.state(state), area, buf);
+    }
+}
+
 /// The TuiLoggerWidget shows the logging messages in an endless scrolling view.
 /// It is controlled by a TuiWidgetState for selected events.
 #[derive(Debug, Clone, Copy, PartialEq, Hash)]
@@ -872,3 +2498,127 @@ pub enum TuiLoggerLevelOutput {
     Abbreviated,
     Long,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evt(level: Level, msg: &str) -> ExtLogRecord {
+        ExtLogRecord::new(timestamp::now(), level, "app", msg)
+    }
+    fn messages(inner: &mut TuiLoggerInner) -> Vec<String> {
+        inner.events.iter().map(|evt| evt.msg.clone()).collect()
+    }
+
+    #[test]
+    fn test_collapse_with_sampling() {
+        let mut inner = new_inner();
+        inner.collapse_duplicates = true;
+        inner.sampling.insert("app".to_string(), (2, 0));
+        // Sampling keeps the 1st, 3rd and 5th event
+        for msg in ["a", "a", "a", "b", "c"] {
+            inner.capture(evt(Level::Debug, msg));
+        }
+        assert_eq!(
+            messages(&mut inner),
+            vec!["a", "last message repeated 1 times", "c"]
+        );
+        let seqs: Vec<u64> = inner.events.iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_collapse_duplicates() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut inner = new_inner();
+        inner.collapse_duplicates = true;
+        let counter = calls.clone();
+        inner.capture_filter = Some(Box::new(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            true
+        }));
+        for msg in ["a", "a", "a", "b", "b"] {
+            inner.capture(evt(Level::Info, msg));
+        }
+        // The trailing run is summarized at the end of the batch
+        inner.end_batch();
+        inner.capture(evt(Level::Info, "b"));
+        inner.end_batch();
+        inner.capture(evt(Level::Warn, "b"));
+        assert_eq!(
+            messages(&mut inner),
+            vec![
+                "a",
+                "last message repeated 2 times",
+                "b",
+                "last message repeated 1 times",
+                "last message repeated 1 times",
+                "b"
+            ]
+        );
+        // Each event has passed the capture filter once
+        assert_eq!(calls.load(Ordering::Relaxed), 7);
+    }
+
+    #[test]
+    fn test_level_config_generation() {
+        let mut config = LevelConfig::new();
+        config.set("a", LevelFilter::Info);
+        config.set("b", LevelFilter::Debug);
+        config.set("c", LevelFilter::Warn);
+        assert_eq!(config.generation, 3);
+        // Setting an unchanged level keeps the generation
+        config.set("a", LevelFilter::Info);
+        assert_eq!(config.generation, 3);
+
+        assert_eq!(config.remove("a"), Some(LevelFilter::Info));
+        assert_eq!(config.remove("a"), None);
+        assert_eq!(config.generation, 4);
+        config.retain(|_, level| level != LevelFilter::Debug);
+        assert_eq!(config.generation, 5);
+        config.retain(|_, _| true);
+        assert_eq!(config.generation, 5);
+        assert_eq!(config.keys().collect::<Vec<_>>(), ["c"]);
+        config.clear();
+        config.clear();
+        assert_eq!(config.generation, 6);
+        assert!(config.get("c").is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_level_config_serde() {
+        let mut origin = LevelConfig::new();
+        origin.set("zeta", LevelFilter::Warn);
+        origin.set("alpha", LevelFilter::Debug);
+        origin.set("mid", LevelFilter::Trace);
+        origin.set_default_display_level(LevelFilter::Info);
+
+        // The targets are written sorted, and without the generations
+        let json = serde_json::to_string(&origin).unwrap();
+        assert_eq!(
+            json,
+            r#"{"targets":{"alpha":"DEBUG","mid":"TRACE","zeta":"WARN"},"default_display_level":"INFO"}"#
+        );
+        let restored: LevelConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!((restored.generation, restored.origin_generation), (0, 0));
+        assert_eq!(restored.get("zeta"), Some(LevelFilter::Warn));
+        assert_eq!(restored.default_display_level, Some(LevelFilter::Info));
+
+        // A restored display config still merges the changes of its origin
+        let mut display: LevelConfig = serde_json::from_str(&json).unwrap();
+        let mut origin: LevelConfig = serde_json::from_str(&json).unwrap();
+        origin.set("new", LevelFilter::Trace);
+        origin.set("zeta", LevelFilter::Error);
+        display.merge(&origin);
+        assert_eq!(display.get("new"), Some(LevelFilter::Info));
+        assert_eq!(display.get("zeta"), Some(LevelFilter::Error));
+        assert_eq!(display.get("mid"), Some(LevelFilter::Trace));
+        assert_eq!(display.generation, origin.generation);
+
+        // Missing fields take their defaults
+        let empty: LevelConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(empty.iter().count(), 0);
+        assert_eq!(empty.default_display_level, None);
+    }
+}
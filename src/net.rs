@@ -0,0 +1,248 @@
+//! Sink streaming newline-delimited records to a remote endpoint.
+use std::io::{self, Write};
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::timestamp;
+use crate::{ExtLogRecord, LogSink};
+
+/// Transport of the [`TuiLoggerNetSink`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TuiLoggerNetProtocol {
+    /// One connection, which is re-established after an error
+    Tcp,
+    /// One datagram per record
+    Udp,
+}
+
+/// Format of the records sent by the [`TuiLoggerNetSink`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TuiLoggerNetFormat {
+    /// `2024-01-31T12:00:00.000000+01:00 INFO target: message`
+    ///
+    /// Backslash, newline and carriage return in the message are escaped as
+    /// `\\`, `\n` and `\r`, so every record is one line.
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Streams the captured events to a TCP/UDP endpoint, e.g. to watch
+/// the log of a headless instance with `nc -l 5555`.
+///
+/// The records are sent by a background thread, which is started on the first record.
+/// If the endpoint is not reachable, the records are dropped and the connection
+/// is retried after the reconnect interval. There is no error policy like for the
+/// file dump, because the errors occur in the background thread after write() has returned.
+pub struct TuiLoggerNetSink {
+    pub addr: String,
+    pub protocol: TuiLoggerNetProtocol,
+    pub format: TuiLoggerNetFormat,
+    pub reconnect_interval: Duration,
+    pub queue_depth: usize,
+    sender: Option<SyncSender<String>>,
+}
+
+impl TuiLoggerNetSink {
+    pub fn new(protocol: TuiLoggerNetProtocol, addr: &str) -> Self {
+        TuiLoggerNetSink {
+            addr: addr.to_string(),
+            protocol,
+            format: TuiLoggerNetFormat::Text,
+            reconnect_interval: Duration::from_secs(5),
+            queue_depth: 1000,
+            sender: None,
+        }
+    }
+    pub fn tcp(addr: &str) -> Self {
+        Self::new(TuiLoggerNetProtocol::Tcp, addr)
+    }
+    pub fn udp(addr: &str) -> Self {
+        Self::new(TuiLoggerNetProtocol::Udp, addr)
+    }
+    pub fn format(mut self, format: TuiLoggerNetFormat) -> Self {
+        self.format = format;
+        self
+    }
+    /// Minimum time between two connection attempts
+    pub fn reconnect_interval(mut self, interval: Duration) -> Self {
+        self.reconnect_interval = interval;
+        self
+    }
+    /// Number of records queued for the background thread. Further records are dropped.
+    pub fn queue_depth(mut self, depth: usize) -> Self {
+        self.queue_depth = depth;
+        self
+    }
+    fn format_record(&self, log_entry: &ExtLogRecord) -> String {
+        match self.format {
+            TuiLoggerNetFormat::Text => format!(
+                "{} {} {}: {}",
+                timestamp::to_rfc3339(&log_entry.timestamp),
+                log_entry.level,
+                log_entry.target,
+                escape_line(&log_entry.msg)
+            ),
+            TuiLoggerNetFormat::Json => record_to_json(log_entry),
+        }
+    }
+}
+
+impl LogSink for TuiLoggerNetSink {
+    fn write(&mut self, log_entry: &ExtLogRecord) {
+        let line = self.format_record(log_entry);
+        if self.sender.is_none() {
+            let (sender, receiver) = mpsc::sync_channel(self.queue_depth);
+            let addr = self.addr.clone();
+            let protocol = self.protocol;
+            let interval = self.reconnect_interval;
+            let spawned = thread::Builder::new()
+                .name("tui-logger::net_sink".into())
+                .spawn(move || run_sender(receiver, protocol, &addr, interval));
+            if spawned.is_err() {
+                return;
+            }
+            self.sender = Some(sender);
+        }
+        if let Some(sender) = self.sender.as_ref() {
+            if let Err(TrySendError::Disconnected(_)) = sender.try_send(line) {
+                self.sender = None;
+            }
+        }
+    }
+}
+
+/// Format the record as one line of JSON, as written by the network sink,
+/// by `serve_events()` and to JSON files
+pub(crate) fn record_to_json(log_entry: &ExtLogRecord) -> String {
+    let timestamp = timestamp::to_rfc3339(&log_entry.timestamp);
+    let mut json = format!(
+        "{{\"seq\":{},\"timestamp\":{},\"level\":{},\"target\":{},\"file\":{},\"line\":{},\"msg\":{}",
+        log_entry.seq,
+        json_string(&timestamp),
+        json_string(log_entry.level.as_str()),
+        json_string(&log_entry.target),
+        json_string(&log_entry.file),
+        log_entry.line,
+        json_string(&log_entry.msg)
+    );
+    if let Some(pid) = log_entry.pid {
+        json.push_str(&format!(",\"pid\":{}", pid));
+    }
+    if !log_entry.key_values.is_empty() {
+        let fields: Vec<String> = log_entry
+            .key_values
+            .iter()
+            .map(|(k, v)| format!("{}:{}", json_string(k), json_string(v)))
+            .collect();
+        json.push_str(&format!(",\"fields\":{{{}}}", fields.join(",")));
+    }
+    json.push('}');
+    json
+}
+
+enum Connection {
+    Tcp(TcpStream),
+    Udp(UdpSocket),
+}
+
+fn connect(protocol: TuiLoggerNetProtocol, addr: &str) -> io::Result<Connection> {
+    match protocol {
+        TuiLoggerNetProtocol::Tcp => {
+            let mut last_err = io::Error::new(io::ErrorKind::NotFound, "no address");
+            for sock_addr in addr.to_socket_addrs()? {
+                match TcpStream::connect_timeout(&sock_addr, Duration::from_secs(1)) {
+                    Ok(stream) => return Ok(Connection::Tcp(stream)),
+                    Err(e) => last_err = e,
+                }
+            }
+            Err(last_err)
+        }
+        TuiLoggerNetProtocol::Udp => {
+            let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+            socket.connect(addr)?;
+            Ok(Connection::Udp(socket))
+        }
+    }
+}
+
+fn run_sender(
+    receiver: Receiver<String>,
+    protocol: TuiLoggerNetProtocol,
+    addr: &str,
+    interval: Duration,
+) {
+    let mut connection = None;
+    let mut last_attempt: Option<Instant> = None;
+    while let Ok(mut line) = receiver.recv() {
+        if connection.is_none() && last_attempt.is_none_or(|t| t.elapsed() >= interval) {
+            last_attempt = Some(Instant::now());
+            connection = connect(protocol, addr).ok();
+        }
+        line.push('\n');
+        let result = match connection.as_mut() {
+            Some(Connection::Tcp(stream)) => stream.write_all(line.as_bytes()),
+            Some(Connection::Udp(socket)) => socket.send(line.as_bytes()).map(|_| ()),
+            None => continue,
+        };
+        if result.is_err() {
+            connection = None;
+        }
+    }
+}
+
+/// Quote and escape the string for JSON
+pub(crate) fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => json.push(ch),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Escape the line breaks, so the message fits on one line
+fn escape_line(msg: &str) -> String {
+    let mut escaped = String::with_capacity(msg.len());
+    for ch in msg.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(
+            json_string("a \"quote\"\\\n\x1b"),
+            "\"a \\\"quote\\\"\\\\\\n\\u001b\""
+        );
+    }
+
+    #[test]
+    fn test_escape_line() {
+        assert_eq!(escape_line("plain"), "plain");
+        assert_eq!(escape_line("a\\b\r\nc\n"), "a\\\\b\\r\\nc\\n");
+    }
+}
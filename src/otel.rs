@@ -0,0 +1,63 @@
+//! Forwarding of the captured events to an OpenTelemetry logger.
+use std::time::SystemTime;
+
+use opentelemetry::logs::{AnyValue, LogRecord, Logger, LoggerProvider, Severity};
+
+use crate::{ExtLogRecord, LogSink};
+
+/// Forwards the captured events to an OpenTelemetry `Logger`, e.g. of an `SdkLoggerProvider`
+/// with the exporter of the existing observability pipeline.
+///
+/// Level, target, timestamp, message and key-value pairs are mapped to the log record.
+/// File and line are added as attributes `code.file.path` and `code.line.number`.
+pub struct TuiLoggerOtel {
+    emit: Box<dyn Fn(&ExtLogRecord) + Send>,
+}
+
+impl TuiLoggerOtel {
+    pub fn new<L>(logger: L) -> Self
+    where
+        L: Logger + Send + 'static,
+    {
+        TuiLoggerOtel {
+            emit: Box::new(move |log_entry| emit(&logger, log_entry)),
+        }
+    }
+    /// Use a logger of the provider named `tui-logger`
+    pub fn from_provider<P>(provider: &P) -> Self
+    where
+        P: LoggerProvider,
+        P::Logger: Send + 'static,
+    {
+        Self::new(provider.logger("tui-logger"))
+    }
+}
+
+impl LogSink for TuiLoggerOtel {
+    fn write(&mut self, log_entry: &ExtLogRecord) {
+        (self.emit)(log_entry)
+    }
+}
+
+fn emit<L: Logger>(logger: &L, log_entry: &ExtLogRecord) {
+    let severity = match log_entry.level {
+        log::Level::Error => Severity::Error,
+        log::Level::Warn => Severity::Warn,
+        log::Level::Info => Severity::Info,
+        log::Level::Debug => Severity::Debug,
+        log::Level::Trace => Severity::Trace,
+    };
+    let mut record = logger.create_log_record();
+    record.set_severity_number(severity);
+    record.set_severity_text(log_entry.level.as_str());
+    record.set_target(log_entry.target.clone());
+    record.set_timestamp(SystemTime::from(log_entry.timestamp));
+    record.set_observed_timestamp(SystemTime::now());
+    record.set_body(AnyValue::from(log_entry.msg.clone()));
+    if log_entry.file != "?" {
+        record.add_attribute("code.file.path", log_entry.file.clone());
+        record.add_attribute("code.line.number", i64::from(log_entry.line));
+    }
+    record.add_attributes(log_entry.key_values.clone());
+    logger.emit(record);
+}
@@ -0,0 +1,110 @@
+//! Remote viewer mode: a running application serves its events over TCP
+//! and another tui-logger instance attaches to populate its own buffer.
+//!
+//! The events are transferred as JSON lines in the format of the network sink.
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, TrySendError};
+use std::thread;
+
+use serde_json::Value;
+
+use crate::net::record_to_json;
+use crate::timestamp;
+use crate::{ExtLogRecord, TUI_LOGGER};
+
+/// Number of events queued per viewer in addition to the buffered events
+const CLIENT_QUEUE_DEPTH: usize = 1000;
+
+/// Serve the events to remote viewers, which connect by [`attach_remote`].
+/// A new viewer receives the buffered events first.
+/// Returns the bound address, e.g. to find the port for address "127.0.0.1:0".
+pub fn serve_events(addr: impl ToSocketAddrs) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+    thread::Builder::new()
+        .name("tui-logger::serve_events".into())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut tli = TUI_LOGGER.inner.lock();
+                let (sender, receiver) = mpsc::sync_channel(tli.events.len() + CLIENT_QUEUE_DEPTH);
+                for log_entry in tli.events.iter() {
+                    let _ = sender.try_send(record_to_json(log_entry));
+                }
+                tli.remote_clients.push(sender);
+                drop(tli);
+                let _ = thread::Builder::new()
+                    .name("tui-logger::remote_client".into())
+                    .spawn(move || write_client(stream, receiver));
+            }
+        })?;
+    Ok(local_addr)
+}
+
+fn write_client(mut stream: TcpStream, receiver: Receiver<String>) {
+    while let Ok(mut line) = receiver.recv() {
+        line.push('\n');
+        if stream.write_all(line.as_bytes()).is_err() {
+            break;
+        }
+    }
+}
+
+/// Forward the event to all viewers. Disconnected viewers are removed.
+pub(crate) fn publish(clients: &mut Vec<mpsc::SyncSender<String>>, log_entry: &ExtLogRecord) {
+    if clients.is_empty() {
+        return;
+    }
+    let line = record_to_json(log_entry);
+    clients.retain(|sender| {
+        !matches!(
+            sender.try_send(line.clone()),
+            Err(TrySendError::Disconnected(_))
+        )
+    });
+}
+
+/// Connect to an application, which serves its events by [`serve_events`],
+/// and feed the received events into the buffer of this instance.
+/// The events keep their timestamp, pid and level, but the log levels are not checked.
+pub fn attach_remote(addr: impl ToSocketAddrs) -> io::Result<()> {
+    let stream = TcpStream::connect(addr)?;
+    thread::Builder::new()
+        .name("tui-logger::attach_remote".into())
+        .spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if let Some(record) = serde_json::from_str(&line).ok().and_then(record_from_json) {
+                    crate::push_record(record);
+                }
+            }
+        })?;
+    Ok(())
+}
+
+fn record_from_json(value: Value) -> Option<ExtLogRecord> {
+    let timestamp = timestamp::parse_rfc3339(value.get("timestamp")?.as_str()?)?;
+    let level = value.get("level")?.as_str()?.parse().ok()?;
+    let target = value.get("target")?.as_str()?;
+    let msg = value.get("msg")?.as_str()?;
+    let mut record = ExtLogRecord::new(timestamp, level, target, msg);
+    if let (Some(file), Some(line)) = (
+        value.get("file").and_then(Value::as_str),
+        value.get("line").and_then(Value::as_u64),
+    ) {
+        record = record.location(file, line as u32);
+    }
+    if let Some(pid) = value.get("pid").and_then(Value::as_u64) {
+        record = record.pid(pid as u32);
+    }
+    if let Some(fields) = value.get("fields").and_then(Value::as_object) {
+        for (key, value) in fields {
+            if let Some(value) = value.as_str() {
+                record = record.key_value(key, value);
+            }
+        }
+    }
+    Some(record)
+}
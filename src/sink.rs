@@ -0,0 +1,48 @@
+//! Output sinks, which receive every captured event in addition to the widgets.
+use std::sync::mpsc::{SyncSender, TrySendError};
+use std::sync::Arc;
+
+use crate::ExtLogRecord;
+
+/// An output for the captured events, e.g. a [`TuiLoggerFile`](crate::TuiLoggerFile).
+///
+/// The sinks are called by move_events() in order of the events.
+/// Own sinks can be implemented, too, and are registered by [`add_sink`](crate::add_sink).
+pub trait LogSink: Send {
+    /// Write the event. Errors have to be handled by the sink, e.g. by ignoring them.
+    fn write(&mut self, log_entry: &ExtLogRecord);
+    /// Called after each batch of events written by move_events()
+    fn end_batch(&mut self) {}
+    /// Flush buffered output, e.g. before exiting. Called by [`flush_sinks`](crate::flush_sinks).
+    fn flush(&mut self) {}
+}
+
+/// Sink calling the hook added by [`add_event_hook`](crate::add_event_hook)
+pub(crate) struct EventHook(pub Box<dyn Fn(&ExtLogRecord) + Send + Sync>);
+
+impl LogSink for EventHook {
+    fn write(&mut self, log_entry: &ExtLogRecord) {
+        (self.0)(log_entry);
+    }
+}
+
+/// Number of events queued for the receiver returned by [`event_receiver`](crate::event_receiver)
+pub(crate) const EVENT_CHANNEL_DEPTH: usize = 1000;
+
+/// Sink sending the events to the receiver. None after the receiver has been dropped.
+pub(crate) struct ChannelSink(pub Option<SyncSender<Arc<ExtLogRecord>>>);
+
+impl LogSink for ChannelSink {
+    fn write(&mut self, log_entry: &ExtLogRecord) {
+        if let Some(sender) = self.0.as_ref() {
+            if let Err(TrySendError::Disconnected(_)) = sender.try_send(Arc::new(log_entry.clone()))
+            {
+                self.0 = None;
+            }
+        }
+    }
+}
+
+/// Identifies a sink registered by [`add_sink`](crate::add_sink)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SinkId(pub(crate) u64);
@@ -1,7 +1,7 @@
 //! `slog` support for `tui-logger`
 
 use super::TUI_LOGGER;
-use log::{self, Log, Record};
+use log::{self, Record};
 use slog::{self, Drain, KV};
 use std::{fmt, io};
 
@@ -28,31 +28,61 @@ impl<W: io::Write> slog::Serializer for Ksv<W> {
     }
 }
 
+/// Serializer collecting the key-value pairs
+#[derive(Default)]
+struct KvCollector(Vec<(String, String)>);
+
+impl slog::Serializer for KvCollector {
+    fn emit_arguments(&mut self, key: slog::Key, val: &fmt::Arguments) -> slog::Result {
+        self.0.push((key.to_string(), val.to_string()));
+        Ok(())
+    }
+}
+
 // Copied from `slog-stdlog`
 struct LazyLogString<'a> {
     info: &'a slog::Record<'a>,
     logger_values: &'a slog::OwnedKVList,
+    context: TuiSlogContext,
 }
 
 impl<'a> LazyLogString<'a> {
-    fn new(info: &'a slog::Record, logger_values: &'a slog::OwnedKVList) -> Self {
+    fn new(
+        info: &'a slog::Record,
+        logger_values: &'a slog::OwnedKVList,
+        context: TuiSlogContext,
+    ) -> Self {
         LazyLogString {
             info,
             logger_values,
+            context,
         }
     }
 }
 
 impl<'a> fmt::Display for LazyLogString<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.context == TuiSlogContext::Prefix {
+            let mut kv = KvCollector::default();
+            self.logger_values
+                .serialize(self.info, &mut kv)
+                .map_err(|_| fmt::Error)?;
+            if !kv.0.is_empty() {
+                let prefix: Vec<String> =
+                    kv.0.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+                write!(f, "[{}] ", prefix.join(" "))?;
+            }
+        }
         write!(f, "{}", self.info.msg())?;
 
         let io = io::Cursor::new(Vec::new());
         let mut ser = Ksv::new(io);
 
-        self.logger_values
-            .serialize(self.info, &mut ser)
-            .map_err(|_| fmt::Error)?;
+        if self.context == TuiSlogContext::Suffix {
+            self.logger_values
+                .serialize(self.info, &mut ser)
+                .map_err(|_| fmt::Error)?;
+        }
         self.info
             .kv()
             .serialize(self.info, &mut ser)
@@ -78,7 +108,49 @@ impl<'a> fmt::Display for LazyLogString<'a> {
 ///     info!(log, "Logging via slog works!");
 ///
 ///  }
-pub struct TuiSlogDrain;
+///  ```
+#[derive(Default)]
+pub struct TuiSlogDrain {
+    context: TuiSlogContext,
+    critical: TuiSlogCritical,
+}
+
+/// Output of the values attached to the logger, e.g. by `Logger::new(o!(...))`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TuiSlogContext {
+    /// Append the values to the message, e.g. `msg, key: value`. This is the default.
+    #[default]
+    Suffix,
+    /// Prefix the message with the values, e.g. `[key=value] msg`
+    Prefix,
+    /// Store the values only as key-value pairs, e.g. to show them as columns
+    Fields,
+}
+
+/// Representation of slog's `Critical` level, which has no equivalent in `log`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TuiSlogCritical {
+    /// Log as `Error` without any distinction. This is the default.
+    #[default]
+    Error,
+    /// Log as `Error` and add the key-value pair `severity: CRITICAL` to the record,
+    /// e.g. to show it with `output_kv_columns()` of the widget.
+    Flagged,
+}
+
+impl TuiSlogDrain {
+    /// Define the output of the values attached to the logger and its parents.
+    /// In any case, these values are stored as key-value pairs of the record.
+    pub fn context(mut self, context: TuiSlogContext) -> Self {
+        self.context = context;
+        self
+    }
+    /// Define how records of level `Critical` are represented
+    pub fn critical(mut self, critical: TuiSlogCritical) -> Self {
+        self.critical = critical;
+        self
+    }
+}
 
 impl Drain for TuiSlogDrain {
     type Ok = ();
@@ -98,15 +170,29 @@ impl Drain for TuiSlogDrain {
             target = info.module();
         }
 
-        let lazy = LazyLogString::new(info, logger_values);
-        TUI_LOGGER.log(
+        if !TUI_LOGGER.module_level_enabled(level, target, Some(info.module())) {
+            return Ok(());
+        }
+        // The key-value pairs are kept in the message and stored as structured fields
+        let mut kv = KvCollector::default();
+        logger_values
+            .serialize(info, &mut kv)
+            .and_then(|_| info.kv().serialize(info, &mut kv))
+            .map_err(io::Error::other)?;
+        if info.level() == slog::Level::Critical && self.critical == TuiSlogCritical::Flagged {
+            kv.0.push(("severity".to_string(), "CRITICAL".to_string()));
+        }
+        let lazy = LazyLogString::new(info, logger_values, self.context);
+        TUI_LOGGER.raw_log_with(
             &Record::builder()
                 .args(format_args!("{}", lazy))
                 .level(level)
                 .target(target)
+                .module_path(Some(info.module()))
                 .file(Some(info.file()))
                 .line(Some(info.line()))
                 .build(),
+            |log_entry| log_entry.key_values.extend(kv.0),
         );
 
         Ok(())
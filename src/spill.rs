@@ -0,0 +1,607 @@
+//! Tier for the events evicted from the buffer: Either spilled to disk or kept in memory
+//! as compressed blocks.
+//!
+//! Each event is stored as one line of tab separated fields:
+//! seq, timestamp, level, pid, target, module path, file, line and message.
+//! Key-value pairs, spans and backtraces are not kept.
+//!
+//! For scrolling back, only a window of chunks around the viewed event is loaded.
+//! A chunk is a run of events in the file, of which the offset is indexed, or one compressed
+//! block. The file is read by [SpillRead] without the logger locked.
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::timestamp;
+use crate::ExtLogRecord;
+
+/// Number of events per chunk of the file
+const INDEX_STEP: usize = 256;
+/// Number of chunks loaded on each side of the viewed chunk
+const WINDOW_CHUNKS: usize = 8;
+
+enum Storage {
+    File {
+        path: PathBuf,
+        writer: BufWriter<File>,
+        /// Sequence number and byte offset of the first event of each chunk
+        index: Vec<(u64, u64)>,
+        /// Number of written events and bytes
+        count: usize,
+        len: u64,
+    },
+    #[cfg(feature = "gzip")]
+    Compressed(Blocks),
+}
+
+/// Predicate of [Spill::retain()] for the events spilled before the given sequence number
+type Filter = (u64, Arc<dyn Fn(&ExtLogRecord) -> bool + Send + Sync>);
+
+/// The loaded spilled events
+struct Window {
+    chunks: Range<usize>,
+    events: Vec<ExtLogRecord>,
+}
+
+pub struct Spill {
+    storage: Storage,
+    /// Incremented, whenever the spilled events are rewritten, so a pending read is discarded
+    generation: u64,
+    window: Option<Window>,
+    /// The spilled events are not rewritten by retain(), but filtered on loading
+    filters: Vec<Filter>,
+    /// Sequence number after the newest spilled event
+    next_seq: u64,
+}
+
+/// A range of the spill file to be read without the logger locked.
+/// The events are handed over to [Spill::load()] afterwards.
+pub struct SpillRead {
+    generation: u64,
+    path: PathBuf,
+    chunks: Range<usize>,
+    start: u64,
+    end: u64,
+    /// The range reaches the end of the file
+    tail: bool,
+    filters: Vec<Filter>,
+}
+
+impl SpillRead {
+    pub fn read(&self) -> Vec<ExtLogRecord> {
+        let events = read_range(&self.path, self.start, self.end).unwrap_or_default();
+        filter(&self.filters, events)
+    }
+}
+
+impl Spill {
+    /// Create the file. An existing file is truncated.
+    pub fn create(path: &Path) -> io::Result<Spill> {
+        Ok(Spill {
+            storage: Storage::File {
+                path: path.to_path_buf(),
+                writer: BufWriter::new(File::create(path)?),
+                index: vec![],
+                count: 0,
+                len: 0,
+            },
+            generation: 0,
+            window: None,
+            filters: vec![],
+            next_seq: 0,
+        })
+    }
+    /// Keep the events in memory, compressed in blocks of `block_size` events.
+    /// The oldest blocks are dropped, if the compressed blocks exceed `max_bytes`.
+    #[cfg(feature = "gzip")]
+    pub fn compressed(block_size: usize, max_bytes: usize) -> Spill {
+        Spill {
+            storage: Storage::Compressed(Blocks::new(block_size, max_bytes)),
+            generation: 0,
+            window: None,
+            filters: vec![],
+            next_seq: 0,
+        }
+    }
+    /// Append the event. A failed write to the spill file loses the event.
+    pub fn push(&mut self, evt: ExtLogRecord) {
+        let tail = self
+            .window
+            .as_ref()
+            .is_some_and(|window| window.chunks.end == self.chunks());
+        self.write(&evt);
+        let (first, chunks) = (self.first_chunk(), self.chunks());
+        if let Some(window) = self.window.as_mut() {
+            if window.chunks.start < first {
+                // The window must not outlive its dropped blocks
+                self.window = None;
+            } else if tail {
+                window.events.push(evt);
+                window.chunks.end = chunks;
+            }
+        }
+    }
+    /// Prepare the window of chunks around the event with the sequence number, or at the end
+    /// for None. Returns the range of the file to read, unless the loaded window covers it.
+    /// Compressed blocks are loaded immediately.
+    pub fn request(&mut self, anchor: Option<u64>) -> Option<SpillRead> {
+        let (first, chunks) = (self.first_chunk(), self.chunks());
+        if chunks == first {
+            self.window = None;
+            return None;
+        }
+        let chunk = anchor.map_or(chunks - 1, |seq| self.chunk_of(seq));
+        let around = |distance: usize| {
+            chunk.saturating_sub(distance).max(first)..(chunk + distance + 1).min(chunks)
+        };
+        if let Some(window) = self.window.as_ref() {
+            // The loaded window is kept, until the anchor comes close to its edges
+            let needed = around(WINDOW_CHUNKS / 2);
+            if window.chunks.start <= needed.start
+                && needed.end <= window.chunks.end
+                && window.chunks.len() <= 4 * WINDOW_CHUNKS
+            {
+                return None;
+            }
+        }
+        let wanted = around(WINDOW_CHUNKS);
+        match &mut self.storage {
+            Storage::File {
+                path,
+                writer,
+                index,
+                len,
+                ..
+            } => {
+                // The reader has to see all written events
+                writer.flush().ok()?;
+                Some(SpillRead {
+                    generation: self.generation,
+                    path: path.clone(),
+                    start: index[wanted.start].1,
+                    end: index.get(wanted.end).map_or(*len, |(_, offset)| *offset),
+                    tail: wanted.end == index.len(),
+                    chunks: wanted,
+                    filters: self.filters.clone(),
+                })
+            }
+            #[cfg(feature = "gzip")]
+            Storage::Compressed(blocks) => {
+                let events = blocks
+                    .read(wanted.clone())
+                    .map(|content| content.lines().filter_map(decode).collect())
+                    .unwrap_or_default();
+                let events = filter(&self.filters, events);
+                self.window = Some(Window {
+                    chunks: wanted,
+                    events,
+                });
+                None
+            }
+        }
+    }
+    /// Take the events read for the window. The read is discarded, if the spilled events
+    /// have been rewritten meanwhile. Events spilled meanwhile are added to a window at the end.
+    pub fn load(&mut self, read: SpillRead, mut events: Vec<ExtLogRecord>) {
+        if read.generation != self.generation {
+            return;
+        }
+        let mut chunks = read.chunks;
+        match &mut self.storage {
+            Storage::File {
+                path,
+                writer,
+                index,
+                len,
+                ..
+            } if read.tail => {
+                if *len > read.end && writer.flush().is_ok() {
+                    let spilled = read_range(path, read.end, *len).unwrap_or_default();
+                    events.extend(filter(&self.filters, spilled));
+                }
+                chunks.end = index.len();
+            }
+            _ => {}
+        }
+        self.window = Some(Window { chunks, events });
+    }
+    pub fn is_file(&self) -> bool {
+        matches!(self.storage, Storage::File { .. })
+    }
+    pub fn unload(&mut self) {
+        self.window = None;
+    }
+    pub fn loaded(&self) -> &[ExtLogRecord] {
+        self.window
+            .as_ref()
+            .map_or(&[], |window| window.events.as_slice())
+    }
+    /// Sequence numbers of the oldest and the newest loaded event,
+    /// if older respectively newer spilled events are not loaded
+    pub fn edges(&self) -> (Option<u64>, Option<u64>) {
+        let Some(window) = self.window.as_ref() else {
+            return (None, None);
+        };
+        let older = window.chunks.start > self.first_chunk();
+        let newer = window.chunks.end < self.chunks();
+        (
+            window.events.first().filter(|_| older).map(|evt| evt.seq),
+            window.events.last().filter(|_| newer).map(|evt| evt.seq),
+        )
+    }
+    pub fn clear(&mut self) {
+        match &mut self.storage {
+            Storage::File {
+                path,
+                writer,
+                index,
+                count,
+                len,
+            } => {
+                if let Ok(file) = File::create(path) {
+                    *writer = BufWriter::new(file);
+                }
+                index.clear();
+                *count = 0;
+                *len = 0;
+            }
+            #[cfg(feature = "gzip")]
+            Storage::Compressed(blocks) => blocks.clear(),
+        }
+        self.generation += 1;
+        self.window = None;
+        self.filters.clear();
+    }
+    /// Keep only the spilled events, for which the predicate returns true.
+    /// The file is not rewritten, but the predicate is applied to the events on loading them.
+    pub fn retain(&mut self, f: Arc<dyn Fn(&ExtLogRecord) -> bool + Send + Sync>) {
+        if let Some(window) = self.window.as_mut() {
+            window.events.retain(|evt| f(evt));
+        }
+        self.filters.push((self.next_seq, f));
+        // A pending read has to be filtered, too
+        self.generation += 1;
+    }
+    /// Number of chunks including the dropped ones
+    fn chunks(&self) -> usize {
+        match &self.storage {
+            Storage::File { index, .. } => index.len(),
+            #[cfg(feature = "gzip")]
+            Storage::Compressed(blocks) => blocks.chunks(),
+        }
+    }
+    /// Number of the oldest available chunk
+    fn first_chunk(&self) -> usize {
+        match &self.storage {
+            Storage::File { .. } => 0,
+            #[cfg(feature = "gzip")]
+            Storage::Compressed(blocks) => blocks.first_block,
+        }
+    }
+    /// The chunk containing the event with the sequence number or the newest one before it
+    fn chunk_of(&self, seq: u64) -> usize {
+        match &self.storage {
+            Storage::File { index, .. } => index
+                .partition_point(|(first, _)| *first <= seq)
+                .saturating_sub(1),
+            #[cfg(feature = "gzip")]
+            Storage::Compressed(blocks) => blocks.chunk_of(seq),
+        }
+    }
+    fn write(&mut self, evt: &ExtLogRecord) {
+        self.next_seq = evt.seq + 1;
+        match &mut self.storage {
+            Storage::File {
+                writer,
+                index,
+                count,
+                len,
+                ..
+            } => {
+                let line = encode(evt);
+                if *count % INDEX_STEP == 0 {
+                    index.push((evt.seq, *len));
+                }
+                let _ = writeln!(writer, "{}", line);
+                *count += 1;
+                *len += line.len() as u64 + 1;
+            }
+            #[cfg(feature = "gzip")]
+            Storage::Compressed(blocks) => blocks.push(&encode(evt), evt.seq),
+        }
+    }
+}
+
+/// Drop the events removed by retain() before they have been spilled
+fn filter(filters: &[Filter], mut events: Vec<ExtLogRecord>) -> Vec<ExtLogRecord> {
+    for (next_seq, f) in filters {
+        events.retain(|evt| evt.seq >= *next_seq || f(evt));
+    }
+    events
+}
+
+fn read_range(path: &Path, start: u64, end: u64) -> io::Result<Vec<ExtLogRecord>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    let reader = BufReader::new(file.take(end - start));
+    Ok(reader
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| decode(&line))
+        .collect())
+}
+
+/// Blocks of encoded events, compressed with deflate
+#[cfg(feature = "gzip")]
+struct Blocks {
+    block_size: usize,
+    max_bytes: usize,
+    /// The compressed blocks with the sequence number of their first event,
+    /// from oldest to newest
+    blocks: std::collections::VecDeque<(Vec<u8>, u64)>,
+    /// Number of the oldest block, counting the dropped ones
+    first_block: usize,
+    compressed_bytes: usize,
+    /// The newest events, which do not fill a block yet
+    pending: String,
+    pending_cnt: usize,
+    pending_seq: u64,
+}
+
+#[cfg(feature = "gzip")]
+impl Blocks {
+    fn new(block_size: usize, max_bytes: usize) -> Blocks {
+        Blocks {
+            block_size: block_size.max(1),
+            max_bytes,
+            blocks: std::collections::VecDeque::new(),
+            first_block: 0,
+            compressed_bytes: 0,
+            pending: String::new(),
+            pending_cnt: 0,
+            pending_seq: 0,
+        }
+    }
+    /// Append the encoded event. The oldest blocks are dropped, if the limit is exceeded.
+    fn push(&mut self, line: &str, seq: u64) {
+        use flate2::{write::DeflateEncoder, Compression};
+
+        if self.pending_cnt == 0 {
+            self.pending_seq = seq;
+        }
+        self.pending.push_str(line);
+        self.pending.push('\n');
+        self.pending_cnt += 1;
+        if self.pending_cnt < self.block_size {
+            return;
+        }
+        let mut encoder = DeflateEncoder::new(vec![], Compression::default());
+        let _ = encoder.write_all(self.pending.as_bytes());
+        let Ok(block) = encoder.finish() else {
+            return;
+        };
+        self.compressed_bytes += block.len();
+        self.blocks.push_back((block, self.pending_seq));
+        self.pending.clear();
+        self.pending_cnt = 0;
+        while self.compressed_bytes > self.max_bytes {
+            let Some((block, _)) = self.blocks.pop_front() else {
+                break;
+            };
+            self.compressed_bytes -= block.len();
+            self.first_block += 1;
+        }
+    }
+    /// Number of blocks including the dropped ones and the pending events
+    fn chunks(&self) -> usize {
+        self.first_block + self.blocks.len() + usize::from(self.pending_cnt > 0)
+    }
+    fn chunk_of(&self, seq: u64) -> usize {
+        let n = if self.pending_cnt > 0 && self.pending_seq <= seq {
+            self.blocks.len() + 1
+        } else {
+            self.blocks.partition_point(|(_, first)| *first <= seq)
+        };
+        self.first_block + n.saturating_sub(1)
+    }
+    /// Decompress the given blocks
+    fn read(&self, chunks: Range<usize>) -> io::Result<String> {
+        use flate2::read::DeflateDecoder;
+
+        let mut content = String::new();
+        for chunk in chunks {
+            match self.blocks.get(chunk - self.first_block) {
+                Some((block, _)) => {
+                    DeflateDecoder::new(block.as_slice()).read_to_string(&mut content)?;
+                }
+                None => content.push_str(&self.pending),
+            }
+        }
+        Ok(content)
+    }
+    fn clear(&mut self) {
+        self.blocks.clear();
+        self.compressed_bytes = 0;
+        self.pending.clear();
+        self.pending_cnt = 0;
+    }
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        match &self.storage {
+            Storage::File { path, .. } => {
+                let _ = fs::remove_file(path);
+            }
+            #[cfg(feature = "gzip")]
+            Storage::Compressed(_) => {}
+        }
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            unescaped.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(ch) => unescaped.push(ch),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+fn encode(evt: &ExtLogRecord) -> String {
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+        evt.seq,
+        timestamp::to_rfc3339(&evt.timestamp),
+        evt.level,
+        evt.pid.map(|pid| pid.to_string()).unwrap_or_default(),
+        escape(&evt.target),
+        evt.module_path.as_deref().map(escape).unwrap_or_default(),
+        escape(&evt.file),
+        evt.line,
+        escape(&evt.msg)
+    )
+}
+
+fn decode(line: &str) -> Option<ExtLogRecord> {
+    let mut fields = line.splitn(9, '\t');
+    let seq = fields.next()?.parse().ok()?;
+    let timestamp = timestamp::parse_rfc3339(fields.next()?)?;
+    let level = fields.next()?.parse().ok()?;
+    let pid = fields.next()?.parse().ok();
+    let target = unescape(fields.next()?);
+    let module_path = Some(fields.next()?)
+        .filter(|path| !path.is_empty())
+        .map(unescape);
+    let file = unescape(fields.next()?);
+    let line_nr = fields.next()?.parse().ok()?;
+    let msg = unescape(fields.next()?);
+    let mut evt = ExtLogRecord::new(timestamp, level, &target, &msg).location(&file, line_nr);
+    evt.seq = seq;
+    evt.pid = pid;
+    evt.module_path = module_path;
+    Some(evt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let mut evt = ExtLogRecord::new(timestamp::now(), log::Level::Warn, "a\tb", "x\\y\nz")
+            .location("src/main.rs", 42)
+            .module_path("app::net")
+            .pid(7);
+        evt.seq = 12;
+        let decoded = decode(&encode(&evt)).unwrap();
+        assert_eq!(decoded.seq, 12);
+        assert_eq!(decoded.timestamp.unix_micros(), evt.timestamp.unix_micros());
+        assert_eq!(decoded.level, log::Level::Warn);
+        assert_eq!(decoded.pid, Some(7));
+        assert_eq!(decoded.target, "a\tb");
+        assert_eq!(decoded.module_path.as_deref(), Some("app::net"));
+        assert_eq!((decoded.file.as_str(), decoded.line), ("src/main.rs", 42));
+        assert_eq!(decoded.msg, "x\\y\nz");
+    }
+
+    fn evt(seq: u64) -> ExtLogRecord {
+        let mut evt = ExtLogRecord::new(timestamp::now(), log::Level::Info, "app", "msg");
+        evt.seq = seq;
+        evt
+    }
+    fn load(spill: &mut Spill, anchor: Option<u64>) {
+        if let Some(read) = spill.request(anchor) {
+            let events = read.read();
+            spill.load(read, events);
+        }
+    }
+
+    #[test]
+    fn test_spill_window() {
+        let path = std::env::temp_dir().join(format!("tui-logger-window-{}", std::process::id()));
+        let mut spill = Spill::create(&path).unwrap();
+        let step = INDEX_STEP as u64;
+        let total = 40 * step;
+        for seq in 0..total {
+            spill.push(evt(seq));
+        }
+        // Only the chunks around the anchor are read
+        load(&mut spill, Some(19 * step + 5));
+        let loaded = spill.loaded();
+        assert_eq!(loaded[0].seq, (19 - WINDOW_CHUNKS as u64) * step);
+        assert_eq!(loaded.len(), (2 * WINDOW_CHUNKS + 1) * INDEX_STEP);
+        assert_eq!(
+            spill.edges(),
+            (
+                Some(loaded[0].seq),
+                Some((20 + WINDOW_CHUNKS as u64) * step - 1)
+            )
+        );
+        // The window is kept for nearby anchors
+        assert!(spill.request(Some(21 * step)).is_none());
+        // Events spilled during the read of the newest events are added to the window
+        let read = spill.request(None).unwrap();
+        let events = read.read();
+        spill.push(evt(total));
+        spill.load(read, events);
+        spill.push(evt(total + 1));
+        let seqs: Vec<u64> = spill
+            .loaded()
+            .iter()
+            .rev()
+            .map(|evt| evt.seq)
+            .take(3)
+            .collect();
+        assert_eq!(seqs, vec![total + 1, total, total - 1]);
+        assert_eq!(spill.edges().1, None);
+        // A read is discarded, if the spilled events have been rewritten meanwhile
+        let read = spill.request(Some(0)).unwrap();
+        let events = read.read();
+        spill.clear();
+        spill.load(read, events);
+        assert!(spill.loaded().is_empty());
+        drop(spill);
+        assert!(!path.exists());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_compressed_window() {
+        let mut spill = Spill::compressed(2, usize::MAX);
+        for seq in 0..100 {
+            spill.push(evt(seq));
+        }
+        // Blocks are loaded without a read of a file
+        assert!(spill.request(Some(51)).is_none());
+        let loaded = spill.loaded();
+        assert_eq!(loaded[0].seq, 2 * (25 - WINDOW_CHUNKS as u64));
+        assert_eq!(loaded.len(), 2 * (2 * WINDOW_CHUNKS + 1));
+    }
+}
@@ -0,0 +1,563 @@
+//! Storage of the moved events, which are displayed by the widgets.
+use std::collections::{HashMap, VecDeque};
+use std::iter::Peekable;
+use std::mem;
+use std::sync::Arc;
+
+use log::Level;
+
+use crate::spill::{Spill, SpillRead};
+use crate::{CircularBuffer, ExtLogRecord};
+
+/// Layout of the buffers
+enum Storage {
+    /// One circular buffer for all events
+    Main(CircularBuffer<ExtLogRecord>),
+    /// One circular buffer per level indexed from Error to Trace
+    PerLevel(Vec<CircularBuffer<ExtLogRecord>>),
+    PerTarget(TargetBuffers),
+    Memory(MemoryBuffer),
+}
+
+/// The buffers of the events with optional retention of severe events.
+///
+/// Events are iterated in order of their sequence number.
+pub struct EventStore {
+    storage: Storage,
+    /// Error and warning events, which have been evicted from the main buffer
+    /// or the memory limited buffer. These are all older than the events in the buffer.
+    retained: CircularBuffer<ExtLogRecord>,
+    /// Receives all evicted events instead of the retained buffer. The spilled events
+    /// are newer than the retained ones and older than the events in the buffer.
+    spill: Option<Spill>,
+}
+
+/// Bounded buffers per target with a global cap
+struct TargetBuffers {
+    depth: usize,
+    total: usize,
+    len: usize,
+    buffers: HashMap<String, VecDeque<ExtLogRecord>>,
+}
+
+impl TargetBuffers {
+    fn push(&mut self, evt: ExtLogRecord) {
+        if self.depth == 0 || self.total == 0 {
+            return;
+        }
+        let buffer = self.buffers.entry(evt.target.clone()).or_default();
+        if buffer.len() >= self.depth {
+            buffer.pop_front();
+        } else {
+            self.len += 1;
+        }
+        buffer.push_back(evt);
+        if self.len > self.total {
+            // Evict from the target with the most events, so no target evicts all others
+            if let Some(largest) = self.buffers.values_mut().max_by_key(|buffer| buffer.len()) {
+                largest.pop_front();
+                self.len -= 1;
+            }
+        }
+    }
+}
+
+/// Events limited by their approximate memory usage
+struct MemoryBuffer {
+    limit: usize,
+    used: usize,
+    events: VecDeque<ExtLogRecord>,
+}
+
+impl MemoryBuffer {
+    /// Returns the evicted events
+    fn push(&mut self, evt: ExtLogRecord) -> Vec<ExtLogRecord> {
+        self.used += evt.memory_usage();
+        self.events.push_back(evt);
+        let mut evicted = vec![];
+        while self.used > self.limit {
+            let Some(oldest) = self.events.pop_front() else {
+                break;
+            };
+            self.used -= oldest.memory_usage();
+            evicted.push(oldest);
+        }
+        evicted
+    }
+}
+
+impl EventStore {
+    pub fn new(depth: usize) -> EventStore {
+        EventStore {
+            storage: Storage::Main(CircularBuffer::new(depth)),
+            retained: CircularBuffer::new(0),
+            spill: None,
+        }
+    }
+    /// Remove all events and use only the main buffer with the given depth
+    pub fn set_depth(&mut self, depth: usize) {
+        self.clear();
+        self.storage = Storage::Main(CircularBuffer::new(depth));
+    }
+    /// Use one buffer per level and set the depth of the level's buffer, which is cleared.
+    /// On switching from another layout, all events are removed
+    /// and the other levels get the depth of the main buffer.
+    pub fn set_level_depth(&mut self, level: Level, depth: usize) {
+        if !matches!(self.storage, Storage::PerLevel(_)) {
+            let main_depth = match &self.storage {
+                Storage::Main(main) => main.capacity(),
+                _ => depth,
+            };
+            self.clear();
+            self.storage =
+                Storage::PerLevel((0..5).map(|_| CircularBuffer::new(main_depth)).collect());
+        }
+        if let Storage::PerLevel(buffers) = &mut self.storage {
+            buffers[level as usize - 1] = CircularBuffer::new(depth);
+        }
+    }
+    /// Remove all events and use one buffer of the given depth per target.
+    /// If the total number of events exceeds `total`, the oldest event of the target
+    /// with the most events is removed.
+    pub fn set_target_depth(&mut self, depth: usize, total: usize) {
+        self.clear();
+        self.storage = Storage::PerTarget(TargetBuffers {
+            depth,
+            total,
+            len: 0,
+            buffers: HashMap::new(),
+        });
+    }
+    /// Limit the events by their approximate memory usage in bytes instead of their number.
+    /// The events of the main buffer are kept as far as they fit.
+    pub fn set_memory_limit(&mut self, limit: usize) {
+        let events = match &mut self.storage {
+            Storage::Main(main) => take(main),
+            Storage::Memory(memory) => mem::take(&mut memory.events).into(),
+            _ => {
+                self.clear();
+                vec![]
+            }
+        };
+        let mut memory = MemoryBuffer {
+            limit,
+            used: 0,
+            events: VecDeque::new(),
+        };
+        for evt in events {
+            memory.push(evt);
+        }
+        self.storage = Storage::Memory(memory);
+    }
+    /// Write the evicted events to the given file, or stop spilling for None.
+    /// A previous file is removed.
+    pub fn set_spill_file(&mut self, path: Option<&std::path::Path>) -> std::io::Result<()> {
+        self.spill = path.map(Spill::create).transpose()?;
+        Ok(())
+    }
+    /// Stop spilling to a file. The returned spill removes the file, when it is dropped.
+    pub fn take_spill_file(&mut self) -> Option<Spill> {
+        if self.spill.as_ref().is_some_and(Spill::is_file) {
+            self.spill.take()
+        } else {
+            None
+        }
+    }
+    /// Keep the evicted events in memory as compressed blocks, or stop for a block size of 0.
+    /// This replaces a spill file.
+    #[cfg(feature = "gzip")]
+    pub fn set_compressed(&mut self, block_size: usize, max_bytes: usize) {
+        self.spill = (block_size > 0).then(|| Spill::compressed(block_size, max_bytes));
+    }
+    /// Prepare the spilled events around the anchor, or the newest ones for None,
+    /// for scrolling back. Returns the read to be done without the logger locked,
+    /// if the loaded events do not cover the anchor.
+    pub fn request_spilled(&mut self, anchor: Option<u64>) -> Option<SpillRead> {
+        self.spill.as_mut()?.request(anchor)
+    }
+    /// Make the spilled events read for scrolling back available
+    pub fn load_spilled(&mut self, read: SpillRead, events: Vec<ExtLogRecord>) {
+        if let Some(spill) = self.spill.as_mut() {
+            spill.load(read, events);
+        }
+    }
+    /// Sequence numbers of the oldest and the newest loaded spilled event,
+    /// beyond which further spilled events have to be loaded first
+    pub fn spilled_edges(&self) -> (Option<u64>, Option<u64>) {
+        self.spill
+            .as_ref()
+            .map_or((None, None), |spill| spill.edges())
+    }
+    /// Free the memory of the loaded spilled events
+    pub fn unload_spilled(&mut self) {
+        if let Some(spill) = self.spill.as_mut() {
+            spill.unload();
+        }
+    }
+    /// Set the depth of the buffer for evicted error and warning events. 0 disables it.
+    /// Retained events are kept as far as they fit.
+    pub fn set_retained_depth(&mut self, depth: usize) {
+        let mut retained = CircularBuffer::new(depth);
+        if depth > 0 {
+            for evt in take(&mut self.retained) {
+                retained.push(evt);
+            }
+        }
+        self.retained = retained;
+    }
+    pub fn push(&mut self, evt: ExtLogRecord) {
+        match &mut self.storage {
+            Storage::Main(main) => {
+                if let Some(evicted) = main.push_evict(evt) {
+                    self.evicted(evicted);
+                }
+            }
+            Storage::PerLevel(buffers) => {
+                let buffer = &mut buffers[evt.level as usize - 1];
+                if buffer.capacity() > 0 {
+                    buffer.push(evt);
+                }
+            }
+            Storage::PerTarget(targets) => targets.push(evt),
+            Storage::Memory(memory) => {
+                for evicted in memory.push(evt) {
+                    self.evicted(evicted);
+                }
+            }
+        }
+    }
+    /// Handle an event evicted from the main buffer or the memory limited buffer
+    fn evicted(&mut self, evt: ExtLogRecord) {
+        if let Some(spill) = self.spill.as_mut() {
+            spill.push(evt);
+        } else if evt.level <= Level::Warn && self.retained.capacity() > 0 {
+            self.retained.push(evt);
+        }
+    }
+    pub fn len(&self) -> usize {
+        let len = match &self.storage {
+            Storage::Main(main) => main.len(),
+            Storage::PerLevel(buffers) => buffers.iter().map(|buffer| buffer.len()).sum(),
+            Storage::PerTarget(targets) => targets.len,
+            Storage::Memory(memory) => memory.events.len(),
+        };
+        len + self.retained.len() + self.spill.as_ref().map_or(0, |spill| spill.loaded().len())
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn clear(&mut self) {
+        self.retained.clear();
+        if let Some(spill) = self.spill.as_mut() {
+            spill.clear();
+        }
+        match &mut self.storage {
+            Storage::Main(main) => main.clear(),
+            Storage::PerLevel(buffers) => buffers.iter_mut().for_each(|buffer| buffer.clear()),
+            Storage::PerTarget(targets) => {
+                targets.buffers.clear();
+                targets.len = 0;
+            }
+            Storage::Memory(memory) => {
+                memory.events.clear();
+                memory.used = 0;
+            }
+        }
+    }
+    /// Keep only the events, for which the predicate returns true
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: Fn(&ExtLogRecord) -> bool + Send + Sync + 'static,
+    {
+        // The spill applies the predicate later on loading its events
+        let keep = Arc::new(f);
+        let mut f = |evt: &ExtLogRecord| keep(evt);
+        retain(&mut self.retained, &mut f);
+        if let Some(spill) = self.spill.as_mut() {
+            spill.retain(keep.clone());
+        }
+        match &mut self.storage {
+            Storage::Main(main) => retain(main, f),
+            Storage::PerLevel(buffers) => {
+                for buffer in buffers.iter_mut() {
+                    retain(buffer, &mut f);
+                }
+            }
+            Storage::PerTarget(targets) => {
+                for buffer in targets.buffers.values_mut() {
+                    buffer.retain(&mut f);
+                }
+                targets.buffers.retain(|_, buffer| !buffer.is_empty());
+                targets.len = targets.buffers.values().map(|buffer| buffer.len()).sum();
+            }
+            Storage::Memory(memory) => {
+                memory.events.retain(f);
+                memory.used = memory.events.iter().map(|evt| evt.memory_usage()).sum();
+            }
+        }
+    }
+    /// Iterate from the oldest to the newest event
+    pub fn iter(&mut self) -> Box<dyn Iterator<Item = &ExtLogRecord> + '_> {
+        let spilled = self
+            .spill
+            .as_ref()
+            .map(|spill| spill.loaded())
+            .unwrap_or_default();
+        let retained = self.retained.iter().chain(spilled.iter());
+        match &mut self.storage {
+            Storage::Main(main) => Box::new(retained.chain(main.iter())),
+            Storage::PerLevel(buffers) => Box::new(Merge::new(
+                buffers
+                    .iter_mut()
+                    .map(|buffer| Box::new(buffer.iter()) as Events),
+                false,
+            )),
+            Storage::PerTarget(targets) => Box::new(Merge::new(
+                targets
+                    .buffers
+                    .values()
+                    .map(|buffer| Box::new(buffer.iter()) as Events),
+                false,
+            )),
+            Storage::Memory(memory) => Box::new(retained.chain(memory.events.iter())),
+        }
+    }
+    /// Iterate from the newest to the oldest event
+    pub fn rev_iter(&mut self) -> Box<dyn Iterator<Item = &ExtLogRecord> + '_> {
+        let spilled = self
+            .spill
+            .as_ref()
+            .map(|spill| spill.loaded())
+            .unwrap_or_default();
+        let retained = spilled.iter().rev().chain(self.retained.rev_iter());
+        match &mut self.storage {
+            Storage::Main(main) => Box::new(main.rev_iter().chain(retained)),
+            Storage::PerLevel(buffers) => Box::new(Merge::new(
+                buffers
+                    .iter_mut()
+                    .map(|buffer| Box::new(buffer.rev_iter()) as Events),
+                true,
+            )),
+            Storage::PerTarget(targets) => Box::new(Merge::new(
+                targets
+                    .buffers
+                    .values()
+                    .map(|buffer| Box::new(buffer.iter().rev()) as Events),
+                true,
+            )),
+            Storage::Memory(memory) => Box::new(memory.events.iter().rev().chain(retained)),
+        }
+    }
+}
+
+type Events<'a> = Box<dyn Iterator<Item = &'a ExtLogRecord> + 'a>;
+
+/// Iterate over the events of all stores from the oldest to the newest event
+pub fn merged_iter<'a>(stores: impl Iterator<Item = &'a mut EventStore>) -> Events<'a> {
+    Box::new(Merge::new(stores.map(EventStore::iter), false))
+}
+
+/// Iterate over the events of all stores from the newest to the oldest event
+pub fn merged_rev_iter<'a>(stores: impl Iterator<Item = &'a mut EventStore>) -> Events<'a> {
+    Box::new(Merge::new(stores.map(EventStore::rev_iter), true))
+}
+
+/// The events of all buffers in order of their sequence number. The events are merged
+/// while iterating, so a widget taking only a page of events does not sort all of them.
+struct Merge<'a> {
+    buffers: Vec<Peekable<Events<'a>>>,
+    newest_first: bool,
+}
+
+impl<'a> Merge<'a> {
+    fn new(buffers: impl Iterator<Item = Events<'a>>, newest_first: bool) -> Merge<'a> {
+        Merge {
+            buffers: buffers.map(Iterator::peekable).collect(),
+            newest_first,
+        }
+    }
+}
+
+impl<'a> Iterator for Merge<'a> {
+    type Item = &'a ExtLogRecord;
+
+    fn next(&mut self) -> Option<&'a ExtLogRecord> {
+        let mut next: Option<(usize, u64)> = None;
+        for (i, buffer) in self.buffers.iter_mut().enumerate() {
+            if let Some(evt) = buffer.peek() {
+                let first = next.is_none_or(|(_, seq)| (evt.seq > seq) == self.newest_first);
+                if first {
+                    next = Some((i, evt.seq));
+                }
+            }
+        }
+        self.buffers[next?.0].next()
+    }
+}
+
+/// A buffer without capacity cannot take out its elements
+fn take(buffer: &mut CircularBuffer<ExtLogRecord>) -> Vec<ExtLogRecord> {
+    if buffer.capacity() > 0 {
+        buffer.take()
+    } else {
+        vec![]
+    }
+}
+
+fn retain<F>(buffer: &mut CircularBuffer<ExtLogRecord>, f: F)
+where
+    F: FnMut(&ExtLogRecord) -> bool,
+{
+    if buffer.capacity() > 0 {
+        buffer.retain(f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timestamp;
+
+    fn evt(seq: u64, level: Level) -> ExtLogRecord {
+        let mut evt = ExtLogRecord::new(timestamp::now(), level, "test", "msg");
+        evt.seq = seq;
+        evt
+    }
+
+    fn load_spilled(store: &mut EventStore) {
+        if let Some(read) = store.request_spilled(None) {
+            let events = read.read();
+            store.load_spilled(read, events);
+        }
+    }
+
+    #[test]
+    fn test_severe_retention() {
+        let mut store = EventStore::new(3);
+        store.set_retained_depth(2);
+        store.push(evt(0, Level::Error));
+        store.push(evt(1, Level::Trace));
+        store.push(evt(2, Level::Warn));
+        store.push(evt(3, Level::Trace));
+        store.push(evt(4, Level::Trace));
+        store.push(evt(5, Level::Trace));
+        store.push(evt(6, Level::Trace));
+        let seqs: Vec<u64> = store.iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![0, 2, 4, 5, 6]);
+        let seqs: Vec<u64> = store.rev_iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![6, 5, 4, 2, 0]);
+        store.retain(|evt| evt.level != Level::Error);
+        assert_eq!(store.len(), 4);
+    }
+
+    #[test]
+    fn test_per_level_buffers() {
+        let mut store = EventStore::new(2);
+        store.set_level_depth(Level::Trace, 1);
+        store.set_level_depth(Level::Debug, 0);
+        store.push(evt(0, Level::Error));
+        store.push(evt(1, Level::Trace));
+        store.push(evt(2, Level::Debug));
+        store.push(evt(3, Level::Trace));
+        store.push(evt(4, Level::Info));
+        let seqs: Vec<u64> = store.iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![0, 3, 4]);
+        let seqs: Vec<u64> = store.rev_iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![4, 3, 0]);
+        store.set_depth(5);
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_per_target_buffers() {
+        let mut store = EventStore::new(10);
+        store.set_target_depth(3, 4);
+        for (seq, target) in ["a", "b", "b", "b", "b", "b", "a"].iter().enumerate() {
+            let mut evt = evt(seq as u64, Level::Info);
+            evt.target = target.to_string();
+            store.push(evt);
+        }
+        // b keeps only 3 events and is reduced further by the total cap
+        let seqs: Vec<u64> = store.iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![0, 4, 5, 6]);
+        let seqs: Vec<u64> = store.rev_iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![6, 5, 4, 0]);
+        store.retain(|evt| evt.target != "b");
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_memory_limit() {
+        let mut store = EventStore::new(10);
+        for seq in 0..5 {
+            store.push(evt(seq, Level::Info));
+        }
+        let size = evt(0, Level::Info).memory_usage();
+        store.set_memory_limit(3 * size);
+        let seqs: Vec<u64> = store.iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![2, 3, 4]);
+        store.set_retained_depth(5);
+        store.push(evt(5, Level::Error));
+        store.push(evt(6, Level::Info));
+        store.push(evt(7, Level::Info));
+        store.push(evt(8, Level::Info));
+        let seqs: Vec<u64> = store.rev_iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![8, 7, 6, 5]);
+        store.push(evt(9, Level::Info));
+        let seqs: Vec<u64> = store.rev_iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![9, 8, 7, 5]);
+    }
+
+    #[test]
+    fn test_spill() {
+        let path = std::env::temp_dir().join(format!("tui-logger-spill-{}", std::process::id()));
+        let mut store = EventStore::new(2);
+        store.set_spill_file(Some(&path)).unwrap();
+        for seq in 0..5 {
+            store.push(evt(seq, Level::Info));
+        }
+        assert_eq!(store.len(), 2);
+        load_spilled(&mut store);
+        let seqs: Vec<u64> = store.iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![0, 1, 2, 3, 4]);
+        store.push(evt(5, Level::Info));
+        let seqs: Vec<u64> = store.rev_iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![5, 4, 3, 2, 1, 0]);
+        store.retain(|evt| evt.seq % 2 == 0);
+        store.unload_spilled();
+        load_spilled(&mut store);
+        let seqs: Vec<u64> = store.iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![0, 2, 4]);
+        // The predicate does not apply to events spilled afterwards
+        for seq in 6..9 {
+            store.push(evt(seq, Level::Info));
+        }
+        store.unload_spilled();
+        load_spilled(&mut store);
+        let seqs: Vec<u64> = store.iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![0, 2, 4, 6, 7, 8]);
+        store.set_spill_file(None).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_compressed() {
+        let mut store = EventStore::new(2);
+        store.set_compressed(2, usize::MAX);
+        for seq in 0..7 {
+            store.push(evt(seq, Level::Info));
+        }
+        load_spilled(&mut store);
+        let seqs: Vec<u64> = store.iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![0, 1, 2, 3, 4, 5, 6]);
+        // Only the pending event remains
+        store.set_compressed(2, 0);
+        for seq in 7..12 {
+            store.push(evt(seq, Level::Info));
+        }
+        load_spilled(&mut store);
+        let seqs: Vec<u64> = store.iter().map(|evt| evt.seq).collect();
+        assert_eq!(seqs, vec![9, 10, 11]);
+    }
+}
@@ -0,0 +1,146 @@
+//! Sink to a syslog collector using RFC 5424 messages.
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+
+use crate::timestamp;
+use crate::{ExtLogRecord, LogSink, TuiLoggerFileErrorPolicy};
+
+enum SyslogTransport {
+    Udp(UdpSocket),
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+}
+
+/// This closely follows the options of [`TuiLoggerFile`](crate::TuiLoggerFile) but sends the events to syslog.
+///
+/// The target of the event is used as MSGID.
+pub struct TuiLoggerSyslog {
+    transport: SyslogTransport,
+    pub facility: u8,
+    pub hostname: String,
+    pub app_name: String,
+    pub format_output_pid: bool,
+    pub error_policy: TuiLoggerFileErrorPolicy,
+    /// Number of consecutive send errors
+    pub failures: usize,
+}
+
+impl TuiLoggerSyslog {
+    /// Send the messages via UDP to the given address, e.g. "loghost:514"
+    pub fn new_udp(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        socket.connect(addr)?;
+        Ok(Self::with_transport(SyslogTransport::Udp(socket)))
+    }
+    /// Send the messages to a local unix socket, e.g. "/dev/log"
+    #[cfg(unix)]
+    pub fn new_unix(path: &str) -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(path)?;
+        Ok(Self::with_transport(SyslogTransport::Unix(socket)))
+    }
+    fn with_transport(transport: SyslogTransport) -> Self {
+        TuiLoggerSyslog {
+            transport,
+            facility: 1,
+            hostname: "-".to_string(),
+            app_name: std::env::current_exe()
+                .ok()
+                .and_then(|exe| {
+                    exe.file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                })
+                .unwrap_or_else(|| "-".to_string()),
+            format_output_pid: true,
+            error_policy: TuiLoggerFileErrorPolicy::Ignore,
+            failures: 0,
+        }
+    }
+    /// Set the facility code, e.g. 1 for user-level messages (default) or 16 for local0.
+    pub fn facility(mut self, facility: u8) -> Self {
+        self.facility = facility;
+        self
+    }
+    /// Set the hostname. Default is "-" to let the collector fill it in.
+    pub fn hostname(mut self, hostname: &str) -> Self {
+        self.hostname = hostname.to_string();
+        self
+    }
+    /// Set the application name. Default is the name of the executable.
+    pub fn app_name(mut self, app_name: &str) -> Self {
+        self.app_name = app_name.to_string();
+        self
+    }
+    /// Output the id of the process as PROCID
+    pub fn output_pid(mut self, enabled: bool) -> Self {
+        self.format_output_pid = enabled;
+        self
+    }
+    /// Define the handling of send errors like for the file dump
+    pub fn on_error(mut self, policy: TuiLoggerFileErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
+    fn format(&self, log_entry: &ExtLogRecord) -> String {
+        let severity = match log_entry.level {
+            log::Level::Error => 3,
+            log::Level::Warn => 4,
+            log::Level::Info => 6,
+            log::Level::Debug | log::Level::Trace => 7,
+        };
+        let procid = match log_entry.pid {
+            Some(pid) if self.format_output_pid => pid.to_string(),
+            _ => "-".to_string(),
+        };
+        format!(
+            "<{}>1 {} {} {} {} {} - {}",
+            u16::from(self.facility) * 8 + severity,
+            timestamp::to_rfc3339(&log_entry.timestamp),
+            header_field(&self.hostname, 255),
+            header_field(&self.app_name, 48),
+            procid,
+            header_field(&log_entry.target, 32),
+            log_entry.msg
+        )
+    }
+    fn send(&self, log_entry: &ExtLogRecord) -> io::Result<()> {
+        let message = self.format(log_entry);
+        match &self.transport {
+            SyslogTransport::Udp(socket) => socket.send(message.as_bytes()),
+            #[cfg(unix)]
+            SyslogTransport::Unix(socket) => socket.send(message.as_bytes()),
+        }
+        .map(|_| ())
+    }
+}
+
+impl LogSink for TuiLoggerSyslog {
+    fn write(&mut self, log_entry: &ExtLogRecord) {
+        if self.error_policy.disables(self.failures) {
+            return;
+        }
+        match self.send(log_entry) {
+            Ok(()) => self.failures = 0,
+            Err(err) => {
+                self.failures += 1;
+                self.error_policy.handle(self.failures, "syslog", &err);
+            }
+        }
+    }
+}
+
+/// Header fields are limited to printable ASCII without spaces
+fn header_field(value: &str, max_len: usize) -> String {
+    let field: String = value
+        .chars()
+        .filter(|ch| ch.is_ascii_graphic())
+        .take(max_len)
+        .collect();
+    if field.is_empty() {
+        "-".to_string()
+    } else {
+        field
+    }
+}
@@ -0,0 +1,218 @@
+//! The timestamps of the crate.
+//!
+//! All uses of `chrono` are kept in this module: Taking the time, formatting with the
+//! strftime format strings of the widgets and the file dump, and the RFC 3339 format of the
+//! spill file, the network sinks and the parsed input. The rest of the crate only uses
+//! [Timestamp] and the functions here, so a different time library, like the `time` crate,
+//! only needs changes in this module. [Timestamp] is opaque and the public API uses only
+//! types of std, so such a backend can be added without breaking the API.
+use std::ops::{Add, Sub};
+use std::time::{Duration, SystemTime};
+
+use chrono::format::{self, Parsed, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, NaiveTime, SecondsFormat, Utc};
+
+pub(crate) use chrono::TimeDelta;
+
+/// Timestamp of the events in the local timezone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Timestamp(DateTime<Local>);
+
+impl Timestamp {
+    /// The current time of the system
+    pub fn now() -> Timestamp {
+        Timestamp(Local::now())
+    }
+    /// Create the timestamp from the microseconds since the unix epoch.
+    /// Returns None, if the value is out of range.
+    pub fn from_unix_micros(micros: i64) -> Option<Timestamp> {
+        DateTime::from_timestamp_micros(micros).map(|utc| Timestamp(utc.with_timezone(&Local)))
+    }
+    /// Microseconds since the unix epoch
+    pub fn unix_micros(&self) -> i64 {
+        self.0.timestamp_micros()
+    }
+    /// Milliseconds since the unix epoch
+    pub fn unix_millis(&self) -> i64 {
+        self.0.timestamp_millis()
+    }
+    /// Time elapsed since the earlier timestamp, or None, if it is later than this one
+    pub fn duration_since(&self, earlier: Timestamp) -> Option<Duration> {
+        self.delta(&earlier).to_std().ok()
+    }
+    /// Signed difference to the earlier timestamp
+    pub(crate) fn delta(&self, earlier: &Timestamp) -> TimeDelta {
+        self.0 - earlier.0
+    }
+}
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Timestamp {
+        Timestamp(time.into())
+    }
+}
+impl From<Timestamp> for SystemTime {
+    fn from(timestamp: Timestamp) -> SystemTime {
+        timestamp.0.into()
+    }
+}
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+    fn add(self, duration: Duration) -> Timestamp {
+        Timestamp(self.0 + duration)
+    }
+}
+impl Sub<Duration> for Timestamp {
+    type Output = Timestamp;
+    fn sub(self, duration: Duration) -> Timestamp {
+        Timestamp(self.0 - duration)
+    }
+}
+
+/// The current time in the local timezone
+pub(crate) fn now() -> Timestamp {
+    Timestamp::now()
+}
+
+/// Format the timestamp as RFC 3339 with microseconds, e.g. 2024-01-31T12:00:00.000000+01:00
+pub(crate) fn to_rfc3339(timestamp: &Timestamp) -> String {
+    timestamp.0.to_rfc3339_opts(SecondsFormat::Micros, false)
+}
+
+/// Parse an RFC 3339 timestamp with any offset
+pub(crate) fn parse_rfc3339(value: &str) -> Option<Timestamp> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|parsed| Timestamp(parsed.with_timezone(&Local)))
+}
+
+/// Parse the date in the value by the format string, e.g. from the name of a rotated file.
+/// Missing fields, like the time for a daily pattern, are taken as their minimum.
+/// Returns the seconds since the epoch, as if the date was UTC, for sorting,
+/// or None, if the whole value does not match.
+pub(crate) fn parse_date(value: &str, fmt: &str) -> Option<i64> {
+    let mut parsed = Parsed::new();
+    format::parse(&mut parsed, value, StrftimeItems::new(fmt)).ok()?;
+    // Setting a field fails without effect, if the value contains it
+    let _ = parsed.set_day(1);
+    let _ = parsed.set_minute(0);
+    let _ = parsed.set_second(0);
+    let date = parsed.to_naive_date().ok()?;
+    let time = parsed.to_naive_time().unwrap_or(NaiveTime::MIN);
+    Some(date.and_time(time).and_utc().timestamp())
+}
+
+/// The timezone, in which timestamps are shown.
+/// The events are always recorded with the local time, so only the output is affected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TuiLoggerTimezone {
+    #[default]
+    Local,
+    Utc,
+    /// Fixed offset in seconds east of UTC, e.g. 3600 for +01:00.
+    /// An offset of a day or more is shown as UTC.
+    Fixed(i32),
+}
+impl TuiLoggerTimezone {
+    /// Format the timestamp in this timezone with the given format string
+    pub fn format(&self, timestamp: &Timestamp, fmt: &str) -> String {
+        match self {
+            TuiLoggerTimezone::Local => timestamp.0.format(fmt).to_string(),
+            TuiLoggerTimezone::Utc => timestamp.0.with_timezone(&Utc).format(fmt).to_string(),
+            TuiLoggerTimezone::Fixed(secs) => match FixedOffset::east_opt(*secs) {
+                Some(offset) => timestamp.0.with_timezone(&offset).format(fmt).to_string(),
+                None => TuiLoggerTimezone::Utc.format(timestamp, fmt),
+            },
+        }
+    }
+}
+
+/// Source of the timestamps of the captured events.
+///
+/// The default is the [SystemClock]. Tests can install a clock with deterministic,
+/// strictly increasing timestamps by `set_clock()`:
+///```
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::time::Duration;
+/// use tui_logger::{Clock, Timestamp};
+///
+/// struct StepClock {
+///     start: Timestamp,
+///     steps: AtomicU64,
+/// }
+/// impl Clock for StepClock {
+///     fn now(&self) -> Timestamp {
+///         let step = self.steps.fetch_add(1, Ordering::Relaxed);
+///         self.start + Duration::from_millis(step)
+///     }
+/// }
+///
+/// tui_logger::set_clock(Box::new(StepClock {
+///     start: Timestamp::now(),
+///     steps: AtomicU64::new(0),
+/// }));
+///```
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Timestamp;
+}
+
+/// The system's clock in the local timezone
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc3339() {
+        let timestamp = parse_rfc3339("2024-01-31T12:00:00.123456+01:00").unwrap();
+        assert_eq!(parse_rfc3339(&to_rfc3339(&timestamp)), Some(timestamp));
+        assert_eq!(
+            TuiLoggerTimezone::Utc.format(&timestamp, "%H:%M:%S%.3f"),
+            "11:00:00.123"
+        );
+        assert_eq!(
+            TuiLoggerTimezone::Fixed(2 * 3600).format(&timestamp, "%H:%M"),
+            "13:00"
+        );
+        assert_eq!(
+            TuiLoggerTimezone::Fixed(86_400).format(&timestamp, "%H:%M"),
+            "11:00"
+        );
+        assert_eq!(parse_rfc3339("yesterday"), None);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let timestamp = parse_rfc3339("2024-01-31T12:00:00.123456+01:00").unwrap();
+        assert_eq!(timestamp.unix_micros(), 1_706_698_800_123_456);
+        assert_eq!(timestamp.unix_millis(), 1_706_698_800_123);
+        assert_eq!(
+            Timestamp::from_unix_micros(timestamp.unix_micros()),
+            Some(timestamp)
+        );
+        assert_eq!(Timestamp::from(SystemTime::from(timestamp)), timestamp);
+        let later = timestamp + Duration::from_millis(1500);
+        assert_eq!(
+            later.duration_since(timestamp),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(timestamp.duration_since(later), None);
+        assert_eq!(later - Duration::from_millis(1500), timestamp);
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let day = parse_date("app-2024-01-31.log", "app-%Y-%m-%d.log").unwrap();
+        let hour = parse_date("app-2024-01-31-13.log", "app-%Y-%m-%d-%H.log").unwrap();
+        assert_eq!(hour - day, 13 * 3600);
+        assert!(parse_date("app-2024-02.log", "app-%Y-%m.log").unwrap() > day);
+        assert_eq!(parse_date("app-backup.log", "app-%Y-%m-%d.log"), None);
+        assert_eq!(parse_date("app-2024-01-31.log.1", "app-%Y-%m-%d.log"), None);
+    }
+}
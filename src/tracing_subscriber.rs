@@ -1,9 +1,13 @@
 //! `tracing-subscriber` support for `tui-logger`
 
 use super::TUI_LOGGER;
-use log::{self, Log, Record};
+use log::{self, Record};
 use std::collections::BTreeMap;
 use std::fmt;
+use std::time::{Duration, Instant};
+use tracing::span::{Attributes, Id};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::Layer;
 
 #[derive(Default)]
@@ -85,29 +89,96 @@ impl<'a> tracing::field::Visit for ToStringVisitor<'a> {
 ///     tracing::info!("Logging via tracing works!");
 ///  }
 ///  ```
-pub struct TuiTracingSubscriberLayer;
+///
+///  ## Span events
+///  With `span_events(true)`, an entry is logged, when a span is created and when
+///  it is closed. The closing entry contains the span's duration.
+#[derive(Default)]
+pub struct TuiTracingSubscriberLayer {
+    span_events: bool,
+}
+impl TuiTracingSubscriberLayer {
+    /// Log an entry on creation and on close of a span
+    pub fn span_events(mut self, enabled: bool) -> Self {
+        self.span_events = enabled;
+        self
+    }
+}
+
+/// Creation time of a span, stored in the span's extensions
+struct SpanStart(Instant);
+
+fn map_level(level: &tracing::Level) -> log::Level {
+    match *level {
+        tracing::Level::ERROR => log::Level::Error,
+        tracing::Level::WARN => log::Level::Warn,
+        tracing::Level::INFO => log::Level::Info,
+        tracing::Level::DEBUG => log::Level::Debug,
+        tracing::Level::TRACE => log::Level::Trace,
+    }
+}
+
+/// Names of the span and its parents, from the outermost to the innermost
+fn span_names<S>(ctx: &Context<'_, S>, id: &Id) -> Vec<String>
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+{
+    ctx.span_scope(id)
+        .map(|scope| {
+            scope
+                .from_root()
+                .map(|span| span.name().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn log_span_event(
+    metadata: &tracing::Metadata<'_>,
+    args: fmt::Arguments,
+    spans: Vec<String>,
+    span_duration: Option<Duration>,
+) {
+    log_record(
+        &Record::builder()
+            .args(args)
+            .level(map_level(metadata.level()))
+            .target(metadata.target())
+            .file(metadata.file())
+            .line(metadata.line())
+            .module_path(metadata.module_path())
+            .build(),
+        || spans,
+        span_duration,
+    );
+}
+
+fn log_record(
+    record: &Record,
+    spans: impl FnOnce() -> Vec<String>,
+    span_duration: Option<Duration>,
+) {
+    if TUI_LOGGER.module_level_enabled(record.level(), record.target(), record.module_path()) {
+        let spans = spans();
+        TUI_LOGGER.raw_log_with(record, |log_entry| {
+            log_entry.spans = spans;
+            log_entry.span_duration = span_duration;
+        });
+    }
+}
 
 impl<S> Layer<S> for TuiTracingSubscriberLayer
 where
-    S: tracing::Subscriber,
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
 {
-    fn on_event(
-        &self,
-        event: &tracing::Event<'_>,
-        _ctx: tracing_subscriber::layer::Context<'_, S>,
-    ) {
+    fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
         let mut visitor = ToStringVisitor::default();
         event.record(&mut visitor);
 
-        let level = match *event.metadata().level() {
-            tracing::Level::ERROR => log::Level::Error,
-            tracing::Level::WARN => log::Level::Warn,
-            tracing::Level::INFO => log::Level::Info,
-            tracing::Level::DEBUG => log::Level::Debug,
-            tracing::Level::TRACE => log::Level::Trace,
-        };
+        let level = map_level(event.metadata().level());
 
-        TUI_LOGGER.log(
+        // The spans are only looked up for enabled events
+        log_record(
             &Record::builder()
                 .args(format_args!("{}", visitor))
                 .level(level)
@@ -116,6 +187,57 @@ where
                 .line(event.metadata().line())
                 .module_path(event.metadata().module_path())
                 .build(),
+            || {
+                ctx.event_scope(event)
+                    .map(|scope| {
+                        scope
+                            .from_root()
+                            .map(|span| span.name().to_string())
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            },
+            None,
+        );
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if !self.span_events {
+            return;
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        span.extensions_mut().insert(SpanStart(Instant::now()));
+        let mut visitor = ToStringVisitor::default();
+        attrs.record(&mut visitor);
+        log_span_event(
+            span.metadata(),
+            format_args!("span {} opened{}", span.name(), visitor),
+            span_names(&ctx, id),
+            None,
+        );
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        if !self.span_events {
+            return;
+        }
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(elapsed) = span
+            .extensions()
+            .get::<SpanStart>()
+            .map(|start| start.0.elapsed())
+        else {
+            return;
+        };
+        log_span_event(
+            span.metadata(),
+            format_args!("span {} closed", span.name()),
+            span_names(&ctx, &id),
+            Some(elapsed),
         );
     }
 }
@@ -1,20 +1,78 @@
 use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 
-use chrono::{DateTime, Local};
 use log::LevelFilter;
 
+use crate::domain::LogDomain;
 use crate::{
-    set_level_for_target, CircularBuffer, ExtLogRecord, LevelConfig, TuiLoggerFile, TuiWidgetEvent,
+    advance_levelfilter, clear_events, reset_level_for_all_targets, set_level_for_target,
+    store::EventStore, ExtLogRecord, LevelConfig, LogSink, SinkId, Timestamp, TuiLoggerFile,
+    TuiLoggerLostEvents, TuiWidgetEvent, TuiWidgetEventResult, TuiWidgetKey, TuiWidgetKeyMap,
+    TUI_LOGGER,
 };
 
+pub type CaptureFilter = Box<dyn Fn(&ExtLogRecord) -> bool + Send>;
+pub type LostEventsHook = Arc<dyn Fn(&TuiLoggerLostEvents) + Send + Sync>;
+
 pub struct TuiLoggerInner {
     pub hot_depth: usize,
-    pub events: CircularBuffer<ExtLogRecord>,
-    pub dump: Option<TuiLoggerFile>,
+    pub hot_shards: usize,
+    pub move_latency: Duration,
+    pub wake_fraction: f32,
+    pub events: EventStore,
+    pub sinks: Vec<(SinkId, Box<dyn LogSink>)>,
+    pub next_sink_id: u64,
+    /// The sink defined by set_log_file()
+    pub log_file: Option<SinkId>,
+    #[cfg(feature = "remote")]
+    pub remote_clients: Vec<std::sync::mpsc::SyncSender<String>>,
     pub total_events: usize,
+    pub lost_events: usize,
+    /// The most events moved from the hot buffer at once
+    pub hot_high_water: usize,
+    /// Number of stored events per level, indexed by `level as usize - 1`
+    pub level_counts: [usize; 5],
+    /// Time of the last batch moved from the hot buffer
+    pub last_move: Timestamp,
+    pub lost_events_hook: Option<LostEventsHook>,
     pub default: LevelFilter,
     pub targets: LevelConfig,
+    /// Targets dropped by remove_target() and the like, which the target selectors hide,
+    /// until they are known again
+    pub removed_targets: HashSet<String>,
+    pub strip_ansi: bool,
+    /// Sequence number of the next moved event. Not reset by clear_events().
+    pub next_seq: u64,
+    /// Directives of the last set_env_filter(), which apply to the targets below them, too
+    pub env_filter_targets: Vec<(String, LevelFilter)>,
+    /// Events, for which this returns false, are dropped by move_events()
+    pub capture_filter: Option<CaptureFilter>,
+    /// Per target: keep 1 of every N debug and trace events, and the count of these events
+    pub sampling: HashMap<String, (usize, usize)>,
+    pub collapse_duplicates: bool,
+    /// The last stored event and the count of its following duplicates
+    pub last_event: Option<ExtLogRecord>,
+    pub duplicates: usize,
+    pub last_duplicate: Option<ExtLogRecord>,
+    pub domains: Vec<LogDomain>,
+}
+
+/// The filter settings of a [`TuiWidgetState`], which the user has tuned,
+/// e.g. to save them on exit and restore them on next launch.
+/// With feature "serde", it can be (de)serialized in any format supported by serde.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TuiWidgetStateConfig {
+    pub default_display_level: Option<LevelFilter>,
+    /// Display level per target
+    pub display_levels: BTreeMap<String, LevelFilter>,
+    pub hide_off: bool,
+    pub hide_target: bool,
+    pub focus_selected: bool,
+    pub selected_target: Option<String>,
 }
 
 /// This struct contains the shared state of a TuiLoggerWidget and a TuiLoggerTargetWidget.
@@ -29,6 +87,26 @@ impl TuiWidgetState {
             inner: Arc::new(Mutex::new(TuiWidgetInnerState::new())),
         }
     }
+    /// Create a state from a configuration, e.g. shipped with the application, in the format
+    /// written by [`save_levels`](crate::save_levels): `default` is the default display level
+    /// and the section `[targets]` contains the display level per target.
+    /// ```
+    /// let state = tui_logger::TuiWidgetState::from_toml(
+    ///     "default = \"INFO\"\n[targets]\n\"hyper\" = \"WARN\"\n",
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn from_toml(toml: &str) -> std::io::Result<TuiWidgetState> {
+        let (default, targets) = crate::levels::parse_toml(toml)?;
+        let mut state = TuiWidgetState::new();
+        if let Some(levelfilter) = default {
+            state = state.set_default_display_level(levelfilter);
+        }
+        for (target, levelfilter) in targets {
+            state = state.set_level_for_target(&target, levelfilter);
+        }
+        Ok(state)
+    }
     pub fn set_default_display_level(self, levelfilter: LevelFilter) -> TuiWidgetState {
         self.inner.lock().config.default_display_level = Some(levelfilter);
         self
@@ -37,9 +115,136 @@ impl TuiWidgetState {
         self.inner.lock().config.set(target, levelfilter);
         self
     }
+    /// Show only events from source files matching the pattern. Without `*` or `?`,
+    /// the pattern matches any part of the path, e.g. `net/`, otherwise the whole path,
+    /// e.g. `*/db/*.rs`. An empty pattern shows all files again.
+    pub fn set_file_filter(self, pattern: &str) -> TuiWidgetState {
+        self.inner.lock().file_filter = (!pattern.is_empty()).then(|| pattern.to_string());
+        self
+    }
+    /// Set the display level for a module path. It takes precedence over the target's level.
+    pub fn set_level_for_module(
+        self,
+        module_path: &str,
+        levelfilter: LevelFilter,
+    ) -> TuiWidgetState {
+        self.inner
+            .lock()
+            .module_levels
+            .insert(module_path.to_string(), levelfilter);
+        self
+    }
+    /// Set the display levels by a filter in the syntax of RUST_LOG,
+    /// e.g. `info,my_crate=debug,hyper=warn`. A bare level sets the default display level.
+    /// Invalid directives are skipped. Like for RUST_LOG, a directive applies to the targets
+    /// below it, too, e.g. `my_crate` to `my_crate::db`, unless a more specific one is given.
+    pub fn set_display_filter_from_string(self, filter: &str) -> TuiWidgetState {
+        let (default, targets) = crate::levels::parse_env_filter(filter);
+        {
+            let mut inner = self.inner.lock();
+            if let Some(levelfilter) = default {
+                inner.config.default_display_level = Some(levelfilter);
+            }
+            for (target, levelfilter) in targets.iter() {
+                inner.config.set(target, *levelfilter);
+            }
+            inner.config.set_directives(targets);
+        }
+        self
+    }
+    /// Write the events, which the widget displays with the current filters,
+    /// to the file in the default format of TuiLoggerFile. An existing file is overwritten.
+    pub fn export_visible(&self, path: &str) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        crate::export::dump_events(TuiLoggerFile::with_writer(file), Some(&self.inner.lock()))
+    }
+    /// Set the file, to which TuiWidgetEvent::ExportKey exports the displayed events as CSV
+    pub fn set_export_path(self, path: &str) -> TuiWidgetState {
+        self.inner.lock().export_path = Some(path.to_string());
+        self
+    }
+    /// Get the current filter settings
+    pub fn save_config(&self) -> TuiWidgetStateConfig {
+        let inner = self.inner.lock();
+        TuiWidgetStateConfig {
+            default_display_level: inner.config.default_display_level,
+            display_levels: inner
+                .config
+                .iter()
+                .map(|(target, levelfilter)| (target.clone(), *levelfilter))
+                .collect(),
+            hide_off: inner.hide_off,
+            hide_target: inner.hide_target,
+            focus_selected: inner.focus_selected,
+            selected_target: inner.opt_selected_target.clone(),
+        }
+    }
+    /// Apply saved filter settings. The display levels are still limited by the capture levels.
+    /// The selected target is selected on next render, as soon as it is known.
+    pub fn restore_config(&self, config: &TuiWidgetStateConfig) {
+        let mut inner = self.inner.lock();
+        inner.config.default_display_level = config.default_display_level;
+        for (target, levelfilter) in config.display_levels.iter() {
+            inner.config.set(target, *levelfilter);
+        }
+        inner.hide_off = config.hide_off;
+        inner.hide_target = config.hide_target;
+        inner.focus_selected = config.focus_selected;
+        inner.opt_pending_target = config.selected_target.clone();
+    }
     pub fn transition(&mut self, event: TuiWidgetEvent) {
         self.inner.lock().transition(event);
     }
+    /// Same as transition(), but reports if the event has been handled by the widget
+    /// and if a redraw is needed. Unhandled events can be used by the application.
+    pub fn handle_event(&mut self, event: TuiWidgetEvent) -> TuiWidgetEventResult {
+        self.inner.lock().transition(event)
+    }
+    /// Set the display level and the capture level of all targets back to the defaults.
+    ///
+    /// The capture level is the logger's default level as set by `set_default_level()`.
+    /// The display level is the default display level limited to the capture level.
+    pub fn reset_levels(&mut self) {
+        self.inner.lock().reset_levels();
+    }
+    /// Look up the key in the key map and handle the mapped event.
+    /// A key, which is not mapped, is reported as not handled.
+    pub fn handle_key(
+        &mut self,
+        keymap: &TuiWidgetKeyMap,
+        key: TuiWidgetKey,
+    ) -> TuiWidgetEventResult {
+        match keymap.get(key) {
+            Some(event) => self.handle_event(event),
+            None => TuiWidgetEventResult::NotHandled,
+        }
+    }
+    /// Scroll the log view by the given number of lines.
+    /// Negative values scroll up towards older events, positive values down towards newer ones.
+    /// Scrolling down to the newest event continues to follow new events.
+    ///
+    /// The scrolling is performed on next render of the TuiLoggerWidget.
+    pub fn scroll_lines(&mut self, lines: isize) {
+        self.inner.lock().scroll_lines += lines;
+    }
+    /// Scroll the log view by the given number of pages. A page is the widget's height.
+    /// Negative values scroll up towards older events.
+    pub fn scroll_pages(&mut self, pages: isize) {
+        self.inner.lock().scroll_pages += pages;
+    }
+    /// Scroll to the oldest event. Same as the TopKey event.
+    pub fn scroll_to_start(&mut self) {
+        self.inner.lock().transition(TuiWidgetEvent::TopKey);
+    }
+    /// Scroll to the newest event and follow new events. Same as the BottomKey event.
+    pub fn scroll_to_end(&mut self) {
+        self.inner.lock().transition(TuiWidgetEvent::BottomKey);
+    }
+    /// Returns true, if the log view follows new events.
+    /// In page mode or while the view is paused, false is returned.
+    pub fn is_following(&self) -> bool {
+        self.inner.lock().is_following()
+    }
 }
 
 #[derive(Default)]
@@ -47,9 +252,9 @@ pub struct TuiWidgetInnerState {
     pub config: LevelConfig,
     pub nr_items: usize,
     pub selected: usize,
-    pub opt_timestamp_bottom: Option<DateTime<Local>>,
-    pub opt_timestamp_next_page: Option<DateTime<Local>>,
-    pub opt_timestamp_prev_page: Option<DateTime<Local>>,
+    pub opt_seq_bottom: Option<u64>,
+    pub opt_seq_next_page: Option<u64>,
+    pub opt_seq_prev_page: Option<u64>,
     pub opt_selected_target: Option<String>,
     pub opt_selected_visibility_more: Option<LevelFilter>,
     pub opt_selected_visibility_less: Option<LevelFilter>,
@@ -59,13 +264,115 @@ pub struct TuiWidgetInnerState {
     pub hide_off: bool,
     pub hide_target: bool,
     pub focus_selected: bool,
+    pub scroll_to_top: bool,
+    /// Pending scroll requests, which are resolved on next render.
+    /// Negative values scroll towards older events.
+    pub scroll_lines: isize,
+    pub scroll_pages: isize,
+    pub paused: bool,
+    /// Show only the first line of each event instead of wrapping it
+    pub truncate: bool,
+    pub opt_seq_paused: Option<u64>,
+    /// Target file of TuiWidgetEvent::ExportKey
+    pub export_path: Option<String>,
+    /// Target to select on next render, e.g. after restore_config()
+    pub opt_pending_target: Option<String>,
+    /// Display levels per module path, which take precedence over the target's
+    pub module_levels: HashMap<String, LevelFilter>,
+    /// Pattern for the source file of the shown events
+    pub file_filter: Option<String>,
+    /// Log domain of the widget last rendered with this state, e.g. for its export
+    pub domain: Option<String>,
 }
 impl TuiWidgetInnerState {
     pub fn new() -> TuiWidgetInnerState {
         TuiWidgetInnerState::default()
     }
-    fn transition(&mut self, event: TuiWidgetEvent) {
+    pub fn is_following(&self) -> bool {
+        self.opt_seq_bottom.is_none() && !self.scroll_to_top && !self.paused
+    }
+    /// Check the display level filter and the focus for the given event.
+    pub fn is_visible(&self, evt: &ExtLogRecord) -> bool {
+        let module_level = evt
+            .module_path
+            .as_ref()
+            .and_then(|module_path| self.module_levels.get(module_path));
+        if let Some(level) = module_level {
+            if *level < evt.level {
+                return false;
+            }
+        } else if let Some(level) = self.config.get(&evt.target) {
+            if level < evt.level {
+                return false;
+            }
+        } else if let Some(level) = self.config.directive_level(&evt.target) {
+            if level < evt.level {
+                return false;
+            }
+        } else if let Some(level) = self.config.default_display_level {
+            if level < evt.level {
+                return false;
+            }
+        }
+        if let Some(pattern) = self.file_filter.as_ref() {
+            if !file_matches(pattern, &evt.file) {
+                return false;
+            }
+        }
+        if self.focus_selected {
+            if let Some(target) = self.opt_selected_target.as_ref() {
+                if target != &evt.target {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+    /// Check, if the event is shown by the widget.
+    /// In addition to is_visible(), events received after pausing the view are not shown.
+    pub fn is_displayed(&self, evt: &ExtLogRecord) -> bool {
+        if !self.is_visible(evt) {
+            return false;
+        }
+        if self.paused {
+            match self.opt_seq_paused {
+                Some(seq) if evt.seq <= seq => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+    fn reset_levels(&mut self) {
+        let default = reset_level_for_all_targets();
+        let display_level = self
+            .config
+            .default_display_level
+            .map(|lvl| lvl.min(default))
+            .unwrap_or(default);
+        let targets: Vec<String> = self.config.keys().cloned().collect();
+        for target in targets {
+            self.config.set(&target, display_level);
+        }
+    }
+    fn clear_pending_scroll(&mut self) {
+        self.scroll_lines = 0;
+        self.scroll_pages = 0;
+    }
+    /// Change the display level of all targets by one level.
+    fn advance_all_targets(&mut self, more: bool) {
+        let targets: Vec<String> = self.config.keys().cloned().collect();
+        for target in targets {
+            if let Some(levelfilter) = self.config.get(&target) {
+                let (opt_more, opt_less) = advance_levelfilter(levelfilter);
+                if let Some(levelfilter) = if more { opt_more } else { opt_less } {
+                    self.config.set(&target, levelfilter);
+                }
+            }
+        }
+    }
+    fn transition(&mut self, event: TuiWidgetEvent) -> TuiWidgetEventResult {
         use TuiWidgetEvent::*;
+        let generation = self.config.generation;
         match event {
             SpaceKey => {
                 self.hide_off ^= true;
@@ -77,48 +384,219 @@ impl TuiWidgetInnerState {
                 self.focus_selected ^= true;
             }
             UpKey => {
-                if !self.hide_target && self.selected > 0 {
-                    self.selected -= 1;
+                self.opt_pending_target = None;
+                if self.hide_target {
+                    return TuiWidgetEventResult::NotHandled;
                 }
+                if self.selected == 0 {
+                    return TuiWidgetEventResult::Handled;
+                }
+                self.selected -= 1;
             }
             DownKey => {
-                if !self.hide_target && self.selected + 1 < self.nr_items {
-                    self.selected += 1;
+                self.opt_pending_target = None;
+                if self.hide_target {
+                    return TuiWidgetEventResult::NotHandled;
+                }
+                if self.selected + 1 >= self.nr_items {
+                    return TuiWidgetEventResult::Handled;
                 }
+                self.selected += 1;
             }
             LeftKey => {
-                if let Some(selected_target) = self.opt_selected_target.take() {
-                    if let Some(selected_visibility_less) = self.opt_selected_visibility_less.take()
-                    {
-                        self.config.set(&selected_target, selected_visibility_less);
-                    }
+                let Some(selected_target) = self.opt_selected_target.take() else {
+                    return TuiWidgetEventResult::NotHandled;
+                };
+                if let Some(selected_visibility_less) = self.opt_selected_visibility_less.take() {
+                    self.config.set(&selected_target, selected_visibility_less);
                 }
             }
             RightKey => {
-                if let Some(selected_target) = self.opt_selected_target.take() {
-                    if let Some(selected_visibility_more) = self.opt_selected_visibility_more.take()
-                    {
-                        self.config.set(&selected_target, selected_visibility_more);
-                    }
+                let Some(selected_target) = self.opt_selected_target.take() else {
+                    return TuiWidgetEventResult::NotHandled;
+                };
+                if let Some(selected_visibility_more) = self.opt_selected_visibility_more.take() {
+                    self.config.set(&selected_target, selected_visibility_more);
                 }
             }
-            PlusKey => {
-                if let Some(selected_target) = self.opt_selected_target.take() {
-                    if let Some(selected_recording_more) = self.opt_selected_recording_more.take() {
-                        set_level_for_target(&selected_target, selected_recording_more);
-                    }
+            SetDisplayLevel(levelfilter) => {
+                let Some(selected_target) = self.opt_selected_target.clone() else {
+                    return TuiWidgetEventResult::NotHandled;
+                };
+                self.config.set(&selected_target, levelfilter);
+            }
+            SetAllDisplayLevel(levelfilter) => {
+                let targets: Vec<String> = self.config.keys().cloned().collect();
+                for target in targets {
+                    self.config.set(&target, levelfilter);
                 }
             }
+            AllLeftKey => self.advance_all_targets(false),
+            AllRightKey => self.advance_all_targets(true),
+            ResetKey => self.reset_levels(),
+            PlusKey => {
+                let Some(selected_target) = self.opt_selected_target.take() else {
+                    return TuiWidgetEventResult::NotHandled;
+                };
+                let Some(selected_recording_more) = self.opt_selected_recording_more.take() else {
+                    return TuiWidgetEventResult::Handled;
+                };
+                set_level_for_target(&selected_target, selected_recording_more);
+            }
             MinusKey => {
-                if let Some(selected_target) = self.opt_selected_target.take() {
-                    if let Some(selected_recording_less) = self.opt_selected_recording_less.take() {
-                        set_level_for_target(&selected_target, selected_recording_less);
-                    }
+                let Some(selected_target) = self.opt_selected_target.take() else {
+                    return TuiWidgetEventResult::NotHandled;
+                };
+                let Some(selected_recording_less) = self.opt_selected_recording_less.take() else {
+                    return TuiWidgetEventResult::Handled;
+                };
+                set_level_for_target(&selected_target, selected_recording_less);
+            }
+            PrevPageKey => {
+                if self.opt_seq_bottom == self.opt_seq_prev_page {
+                    return TuiWidgetEventResult::Handled;
+                }
+                self.opt_seq_bottom = self.opt_seq_prev_page;
+            }
+            NextPageKey => {
+                if self.opt_seq_bottom.is_none() {
+                    // Not in page mode
+                    return TuiWidgetEventResult::NotHandled;
                 }
+                self.opt_seq_bottom = self.opt_seq_next_page;
+            }
+            ToggleWrapKey => {
+                self.truncate ^= true;
+            }
+            ReloadFilterKey => {
+                crate::reload_env_filter();
             }
-            PrevPageKey => self.opt_timestamp_bottom = self.opt_timestamp_prev_page,
-            NextPageKey => self.opt_timestamp_bottom = self.opt_timestamp_next_page,
-            EscapeKey => self.opt_timestamp_bottom = None,
+            ExportKey => {
+                let Some(path) = self.export_path.as_ref() else {
+                    return TuiWidgetEventResult::NotHandled;
+                };
+                // The key has no way to report a failed export
+                let _ = crate::export::write_csv(path, Some(self));
+                return TuiWidgetEventResult::Handled;
+            }
+            TopKey => {
+                self.scroll_to_top = true;
+                self.clear_pending_scroll();
+            }
+            PauseKey => {
+                self.paused ^= true;
+                if self.paused {
+                    // Freeze the view at the newest event in any buffer, as the widget
+                    // may show a domain's buffer
+                    self.opt_seq_paused = TUI_LOGGER.inner.lock().next_seq.checked_sub(1);
+                } else {
+                    self.opt_seq_paused = None;
+                }
+            }
+            ClearKey => {
+                clear_events();
+                self.scroll_to_top = false;
+                self.clear_pending_scroll();
+                self.opt_seq_bottom = None;
+            }
+            EscapeKey | BottomKey => {
+                if self.opt_seq_bottom.is_none()
+                    && !self.scroll_to_top
+                    && self.scroll_lines == 0
+                    && self.scroll_pages == 0
+                {
+                    // Not in page mode: Escape is left to the application
+                    return if event == EscapeKey {
+                        TuiWidgetEventResult::NotHandled
+                    } else {
+                        TuiWidgetEventResult::Handled
+                    };
+                }
+                self.scroll_to_top = false;
+                self.clear_pending_scroll();
+                self.opt_seq_bottom = None;
+            }
+        }
+        match event {
+            LeftKey
+            | RightKey
+            | SetDisplayLevel(_)
+            | SetAllDisplayLevel(_)
+            | AllLeftKey
+            | AllRightKey
+                if self.config.generation == generation =>
+            {
+                TuiWidgetEventResult::Handled
+            }
+            _ => TuiWidgetEventResult::Redraw,
+        }
+    }
+}
+
+/// Substring match or, if the pattern contains `*` or `?`, glob match of the whole path
+fn file_matches(pattern: &str, file: &str) -> bool {
+    if !pattern.contains(['*', '?']) {
+        return file.contains(pattern);
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let file: Vec<char> = file.chars().collect();
+    // Position after the last `*` and the matched file position, for backtracking
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut f) = (0, 0);
+    while f < file.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == file[f]) {
+            p += 1;
+            f += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            p += 1;
+            star = Some((p, f));
+        } else if let Some((star_p, star_f)) = star {
+            p = star_p;
+            f = star_f + 1;
+            star = Some((star_p, star_f + 1));
+        } else {
+            return false;
         }
     }
+    pattern[p..].iter().all(|ch| *ch == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_matches() {
+        assert!(file_matches("net/", "src/net/tcp.rs"));
+        assert!(!file_matches("net/", "src/file.rs"));
+        assert!(file_matches("*/db/*.rs", "src/db/pool.rs"));
+        assert!(file_matches("src/?b/*", "src/db/pool.rs"));
+        assert!(!file_matches("*/db/*.rs", "src/db/pool.rsx"));
+        assert!(file_matches("*", "?"));
+    }
+
+    #[test]
+    fn test_display_filter_from_string() {
+        let state = TuiWidgetState::new()
+            .set_display_filter_from_string("warn,my_crate=debug,my_crate::db=error");
+        let visible = |target: &str, level: log::Level| {
+            let evt = ExtLogRecord::new(crate::timestamp::now(), level, target, "msg");
+            state.inner.lock().is_visible(&evt)
+        };
+        assert!(visible("my_crate", log::Level::Debug));
+        assert!(visible("my_crate::net", log::Level::Debug));
+        assert!(!visible("my_crate::net", log::Level::Trace));
+        assert!(!visible("my_crate::db::pool", log::Level::Warn));
+        assert!(!visible("my_crate_ext", log::Level::Info));
+        assert!(visible("other", log::Level::Warn));
+
+        // Targets merged from the logger take the level of their directive
+        let mut origin = LevelConfig::new();
+        origin.set("my_crate::net", LevelFilter::Trace);
+        origin.set("other", LevelFilter::Trace);
+        let mut inner = state.inner.lock();
+        inner.config.merge(&origin);
+        assert_eq!(inner.config.get("my_crate::net"), Some(LevelFilter::Debug));
+        assert_eq!(inner.config.get("other"), Some(LevelFilter::Warn));
+    }
 }
@@ -7,5 +7,17 @@ pub trait LogFormatter: Send + Sync {
     /// This must format any event in one or more lines.
     /// Correct wrapping in next line with/without indenting must be performed here.
     /// The parameter width is the available line width
-    fn format(&self, width: usize, evt: &ExtLogRecord) -> Vec<Line>;
+    fn format(&self, width: usize, evt: &ExtLogRecord) -> Vec<Line<'_>>;
+
+    /// Same as format(), but with the previously displayed (older) event, if there is any.
+    /// This allows output relative to the previous event, e.g. the elapsed time.
+    /// The default implementation ignores the previous event.
+    fn format_with_previous(
+        &self,
+        width: usize,
+        evt: &ExtLogRecord,
+        _prev: Option<&ExtLogRecord>,
+    ) -> Vec<Line<'_>> {
+        self.format(width, evt)
+    }
 }
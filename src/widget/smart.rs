@@ -3,15 +3,19 @@ use parking_lot::Mutex;
 use std::sync::Arc;
 
 use log::LevelFilter;
+#[cfg(feature = "widget-ref")]
+use ratatui::widgets::WidgetRef;
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
     style::Style,
     text::Line,
-    widgets::{Block, BorderType, Borders, Widget},
+    widgets::{Block, BorderType, Borders, StatefulWidget, Widget},
 };
 
-use crate::{TuiLoggerLevelOutput, TuiLoggerTargetWidget, TuiWidgetState, TUI_LOGGER};
+use crate::{
+    TuiLoggerLevelOutput, TuiLoggerTargetWidget, TuiLoggerTimezone, TuiWidgetState, TUI_LOGGER,
+};
 
 use super::{inner::TuiWidgetInnerState, standard::TuiLoggerWidget};
 
@@ -26,7 +30,7 @@ pub struct TuiLoggerSmartWidget<'a> {
     border_style: Style,
     border_type: BorderType,
     highlight_style: Option<Style>,
-    logformatter: Option<Box<dyn LogFormatter>>,
+    logformatter: Option<Arc<dyn LogFormatter>>,
     style_error: Option<Style>,
     style_warn: Option<Style>,
     style_debug: Option<Style>,
@@ -39,8 +43,21 @@ pub struct TuiLoggerSmartWidget<'a> {
     format_timestamp: Option<Option<String>>,
     format_output_level: Option<Option<TuiLoggerLevelOutput>>,
     format_output_target: Option<bool>,
+    format_target_max_width: Option<Option<usize>>,
     format_output_file: Option<bool>,
     format_output_line: Option<bool>,
+    follow_indicator: Option<bool>,
+    domain: Option<String>,
+    format_word_wrap: Option<bool>,
+    format_output_index: Option<bool>,
+    format_output_delta: Option<bool>,
+    format_relative_timestamp: Option<bool>,
+    format_timezone: Option<TuiLoggerTimezone>,
+    format_output_pid: Option<bool>,
+    format_kv_columns: Option<Vec<(String, usize)>>,
+    format_output_spans: Option<bool>,
+    format_ansi_styles: Option<bool>,
+    hyperlink_template: Option<Option<String>>,
     state: Arc<Mutex<TuiWidgetInnerState>>,
 }
 impl<'a> Default for TuiLoggerSmartWidget<'a> {
@@ -66,8 +83,21 @@ impl<'a> Default for TuiLoggerSmartWidget<'a> {
             format_timestamp: None,
             format_output_level: None,
             format_output_target: None,
+            format_target_max_width: None,
             format_output_file: None,
             format_output_line: None,
+            follow_indicator: None,
+            domain: None,
+            format_word_wrap: None,
+            format_output_index: None,
+            format_output_delta: None,
+            format_relative_timestamp: None,
+            format_timezone: None,
+            format_output_pid: None,
+            format_kv_columns: None,
+            format_output_spans: None,
+            format_ansi_styles: None,
+            hyperlink_template: None,
             state: Arc::new(Mutex::new(TuiWidgetInnerState::new())),
         }
     }
@@ -155,6 +185,27 @@ impl<'a> TuiLoggerSmartWidget<'a> {
         self.format_output_target = Some(enabled);
         self
     }
+    /// Abbreviate targets longer than the width, e.g. `a::b::c::name`, `…::name` or `…ame`
+    ///
+    /// Default is None, which shows the targets in full length
+    pub fn output_target_max_width(mut self, width: Option<usize>) -> Self {
+        self.format_target_max_width = Some(width);
+        self
+    }
+    /// Enables output of the id of the process, which has logged the event
+    ///
+    /// Default is false
+    pub fn output_pid(mut self, enabled: bool) -> Self {
+        self.format_output_pid = Some(enabled);
+        self
+    }
+    /// Enables output of the chain of enclosing tracing spans
+    ///
+    /// Default is false
+    pub fn output_spans(mut self, enabled: bool) -> Self {
+        self.format_output_spans = Some(enabled);
+        self
+    }
     /// Enables output of file field of event
     ///
     /// Default is true
@@ -169,6 +220,74 @@ impl<'a> TuiLoggerSmartWidget<'a> {
         self.format_output_line = Some(enabled);
         self
     }
+    /// Enables output of the event's sequence number
+    ///
+    /// Default is false
+    pub fn output_index(mut self, enabled: bool) -> Self {
+        self.format_output_index = Some(enabled);
+        self
+    }
+    /// Enables output of the elapsed time since the previous displayed event
+    ///
+    /// Default is false
+    pub fn output_delta(mut self, enabled: bool) -> Self {
+        self.format_output_delta = Some(enabled);
+        self
+    }
+    /// Show the timestamp as time elapsed since init_logger()
+    ///
+    /// Default is false
+    pub fn output_relative_timestamp(mut self, enabled: bool) -> Self {
+        self.format_relative_timestamp = Some(enabled);
+        self
+    }
+    /// Show the timestamp in the given timezone instead of the one set by set_timezone()
+    pub fn output_timezone(mut self, timezone: TuiLoggerTimezone) -> Self {
+        self.format_timezone = Some(timezone);
+        self
+    }
+    /// Show the values of the given keys as columns after the message.
+    /// Each key comes with the width of its value.
+    pub fn output_kv_columns(mut self, columns: Vec<(String, usize)>) -> Self {
+        self.format_kv_columns = Some(columns);
+        self
+    }
+    /// Wrap long lines at word boundaries instead of at the widget's width
+    ///
+    /// Default is false
+    pub fn output_word_wrap(mut self, enabled: bool) -> Self {
+        self.format_word_wrap = Some(enabled);
+        self
+    }
+    /// Apply ANSI color and text attribute sequences in the messages
+    ///
+    /// Default is false
+    pub fn output_ansi_styles(mut self, enabled: bool) -> Self {
+        self.format_ansi_styles = Some(enabled);
+        self
+    }
+    /// Render the file and line fields as OSC 8 hyperlink using the template,
+    /// in which `{file}` and `{line}` are replaced.
+    ///
+    /// Default is None
+    pub fn output_hyperlink(mut self, template: Option<String>) -> Self {
+        self.hyperlink_template = Some(template);
+        self
+    }
+    /// Show `[FOLLOW]` or `[PAUSED]` in the log view's title, depending on
+    /// the log view following new events or being in page mode/paused.
+    ///
+    /// Default is false
+    pub fn follow_indicator(mut self, enabled: bool) -> Self {
+        self.follow_indicator = Some(enabled);
+        self
+    }
+    /// Show the events of the log domain set by [`set_log_domain`](crate::set_log_domain)
+    /// instead of the main buffer.
+    pub fn domain(mut self, name: &str) -> Self {
+        self.domain = Some(name.to_string());
+        self
+    }
     pub fn title_target<T>(mut self, title: T) -> Self
     where
         T: Into<Line<'a>>,
@@ -191,18 +310,31 @@ impl<'a> TuiLoggerSmartWidget<'a> {
 impl<'a> Widget for TuiLoggerSmartWidget<'a> {
     /// Nothing to draw for combo widget
     fn render(self, area: Rect, buf: &mut Buffer) {
+        self.draw(area, buf);
+    }
+}
+#[cfg(feature = "widget-ref")]
+#[cfg_attr(docsrs, doc(cfg(feature = "widget-ref")))]
+impl<'a> WidgetRef for TuiLoggerSmartWidget<'a> {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        self.draw(area, buf);
+    }
+}
+impl<'a> TuiLoggerSmartWidget<'a> {
+    fn draw(&self, area: Rect, buf: &mut Buffer) {
+        TUI_LOGGER.move_events_if_manual();
         let entries_s = {
             let mut tui_lock = TUI_LOGGER.inner.lock();
             let first_timestamp = tui_lock
                 .events
                 .iter()
                 .next()
-                .map(|entry| entry.timestamp.timestamp_millis());
+                .map(|entry| entry.timestamp.unix_millis());
             let last_timestamp = tui_lock
                 .events
                 .rev_iter()
                 .next()
-                .map(|entry| entry.timestamp.timestamp_millis());
+                .map(|entry| entry.timestamp.unix_millis());
             if let Some(first) = first_timestamp {
                 if let Some(last) = last_timestamp {
                     let dt = last - first;
@@ -241,13 +373,26 @@ impl<'a> Widget for TuiLoggerSmartWidget<'a> {
                 .opt_style_debug(self.style_debug)
                 .opt_style_trace(self.style_trace)
                 .opt_output_separator(self.format_separator)
-                .opt_output_timestamp(self.format_timestamp)
+                .opt_output_timestamp(self.format_timestamp.clone())
                 .opt_output_level(self.format_output_level)
                 .opt_output_target(self.format_output_target)
+                .opt_output_target_max_width(self.format_target_max_width)
                 .opt_output_file(self.format_output_file)
                 .opt_output_line(self.format_output_line)
-                .inner_state(self.state);
-            tui_lw.render(area, buf);
+                .opt_output_word_wrap(self.format_word_wrap)
+                .opt_output_index(self.format_output_index)
+                .opt_output_delta(self.format_output_delta)
+                .opt_output_relative_timestamp(self.format_relative_timestamp)
+                .opt_output_timezone(self.format_timezone)
+                .opt_output_pid(self.format_output_pid)
+                .opt_output_kv_columns(self.format_kv_columns.clone())
+                .opt_output_spans(self.format_output_spans)
+                .opt_output_ansi_styles(self.format_ansi_styles)
+                .opt_output_hyperlink(self.hyperlink_template.clone())
+                .opt_follow_indicator(self.follow_indicator)
+                .opt_domain(self.domain.clone())
+                .inner_state(self.state.clone());
+            Widget::render(tui_lw, area, buf);
         } else {
             let mut width: usize = 0;
             {
@@ -275,7 +420,7 @@ impl<'a> Widget for TuiLoggerSmartWidget<'a> {
             let tui_ltw = TuiLoggerTargetWidget::default()
                 .block(
                     Block::default()
-                        .title(self.title_target)
+                        .title(self.title_target.clone())
                         .border_style(self.border_style)
                         .border_type(self.border_type)
                         .borders(Borders::ALL),
@@ -286,7 +431,7 @@ impl<'a> Widget for TuiLoggerSmartWidget<'a> {
                 .opt_style_hide(self.style_hide)
                 .opt_style_show(self.style_show)
                 .inner_state(self.state.clone());
-            tui_ltw.render(chunks[0], buf);
+            Widget::render(tui_ltw, chunks[0], buf);
             let tui_lw = TuiLoggerWidget::default()
                 .block(
                     Block::default()
@@ -295,7 +440,7 @@ impl<'a> Widget for TuiLoggerSmartWidget<'a> {
                         .border_type(self.border_type)
                         .borders(Borders::ALL),
                 )
-                .opt_formatter(self.logformatter)
+                .opt_shared_formatter(self.logformatter.clone())
                 .opt_style(self.style)
                 .opt_style_error(self.style_error)
                 .opt_style_warn(self.style_warn)
@@ -303,13 +448,34 @@ impl<'a> Widget for TuiLoggerSmartWidget<'a> {
                 .opt_style_debug(self.style_debug)
                 .opt_style_trace(self.style_trace)
                 .opt_output_separator(self.format_separator)
-                .opt_output_timestamp(self.format_timestamp)
+                .opt_output_timestamp(self.format_timestamp.clone())
                 .opt_output_level(self.format_output_level)
                 .opt_output_target(self.format_output_target)
+                .opt_output_target_max_width(self.format_target_max_width)
                 .opt_output_file(self.format_output_file)
                 .opt_output_line(self.format_output_line)
+                .opt_output_word_wrap(self.format_word_wrap)
+                .opt_output_index(self.format_output_index)
+                .opt_output_delta(self.format_output_delta)
+                .opt_output_relative_timestamp(self.format_relative_timestamp)
+                .opt_output_timezone(self.format_timezone)
+                .opt_output_pid(self.format_output_pid)
+                .opt_output_kv_columns(self.format_kv_columns.clone())
+                .opt_output_spans(self.format_output_spans)
+                .opt_output_ansi_styles(self.format_ansi_styles)
+                .opt_output_hyperlink(self.hyperlink_template.clone())
+                .opt_follow_indicator(self.follow_indicator)
+                .opt_domain(self.domain.clone())
                 .inner_state(self.state.clone());
-            tui_lw.render(chunks[1], buf);
+            Widget::render(tui_lw, chunks[1], buf);
         }
     }
 }
+
+impl<'a> StatefulWidget for TuiLoggerSmartWidget<'a> {
+    type State = TuiWidgetState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut TuiWidgetState) {
+        Widget::render(self.state(state), area, buf);
+    }
+}
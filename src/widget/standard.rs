@@ -3,20 +3,27 @@ use crate::widget::standard_formatter::LogStandardFormatter;
 use parking_lot::Mutex;
 use std::sync::Arc;
 
+#[cfg(feature = "widget-ref")]
+use ratatui::widgets::WidgetRef;
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
     style::Style,
-    widgets::{Block, Widget},
+    text::Line,
+    widgets::{Block, StatefulWidget, Widget},
 };
 
-use crate::{CircularBuffer, TuiLoggerLevelOutput, TuiWidgetState, TUI_LOGGER};
+use crate::{
+    start_time, timezone, CircularBuffer, ExtLogRecord, TuiLoggerLevelOutput, TuiLoggerTimezone,
+    TuiWidgetState, TUI_LOGGER,
+};
 
-use super::inner::TuiWidgetInnerState;
+use super::inner::{TuiLoggerInner, TuiWidgetInnerState};
+use crate::store::EventStore;
 
 pub struct TuiLoggerWidget<'b> {
     block: Option<Block<'b>>,
-    logformatter: Option<Box<dyn LogFormatter>>,
+    logformatter: Option<Arc<dyn LogFormatter>>,
     /// Base style of the widget
     style: Style,
     /// Level based style
@@ -29,8 +36,21 @@ pub struct TuiLoggerWidget<'b> {
     format_timestamp: Option<String>,
     format_output_level: Option<TuiLoggerLevelOutput>,
     format_output_target: bool,
+    format_target_max_width: Option<usize>,
     format_output_file: bool,
     format_output_line: bool,
+    format_word_wrap: bool,
+    format_ansi_styles: bool,
+    hyperlink_template: Option<String>,
+    format_output_index: bool,
+    format_output_delta: bool,
+    format_relative_timestamp: bool,
+    format_timezone: Option<TuiLoggerTimezone>,
+    format_output_pid: bool,
+    format_kv_columns: Vec<(String, usize)>,
+    format_output_spans: bool,
+    follow_indicator: bool,
+    domain: Option<String>,
     state: Arc<Mutex<TuiWidgetInnerState>>,
 }
 impl<'b> Default for TuiLoggerWidget<'b> {
@@ -49,8 +69,21 @@ impl<'b> Default for TuiLoggerWidget<'b> {
             format_timestamp: Some("%H:%M:%S".to_string()),
             format_output_level: Some(TuiLoggerLevelOutput::Long),
             format_output_target: true,
+            format_target_max_width: None,
             format_output_file: true,
             format_output_line: true,
+            format_word_wrap: false,
+            format_ansi_styles: false,
+            hyperlink_template: None,
+            format_output_index: false,
+            format_output_delta: false,
+            format_relative_timestamp: false,
+            format_timezone: None,
+            format_output_pid: false,
+            format_kv_columns: vec![],
+            format_output_spans: false,
+            follow_indicator: false,
+            domain: None,
             state: Arc::new(Mutex::new(TuiWidgetInnerState::new())),
         }
     }
@@ -61,11 +94,15 @@ impl<'b> TuiLoggerWidget<'b> {
         self
     }
     pub fn opt_formatter(mut self, formatter: Option<Box<dyn LogFormatter>>) -> Self {
-        self.logformatter = formatter;
+        self.logformatter = formatter.map(Arc::from);
         self
     }
     pub fn formatter(mut self, formatter: Box<dyn LogFormatter>) -> Self {
-        self.logformatter = Some(formatter);
+        self.logformatter = Some(Arc::from(formatter));
+        self
+    }
+    pub(crate) fn opt_shared_formatter(mut self, formatter: Option<Arc<dyn LogFormatter>>) -> Self {
+        self.logformatter = formatter;
         self
     }
     pub fn opt_style(mut self, style: Option<Style>) -> Self {
@@ -156,6 +193,31 @@ impl<'b> TuiLoggerWidget<'b> {
         self.format_timestamp = fmt;
         self
     }
+    pub fn opt_output_relative_timestamp(mut self, opt_enabled: Option<bool>) -> Self {
+        if let Some(enabled) = opt_enabled {
+            self.format_relative_timestamp = enabled;
+        }
+        self
+    }
+    /// Show the timestamp as time elapsed since init_logger(), e.g. 00:03:12.456,
+    /// instead of the wall-clock time. The timestamp's format string is not used then.
+    ///
+    /// Default is false
+    pub fn output_relative_timestamp(mut self, enabled: bool) -> Self {
+        self.format_relative_timestamp = enabled;
+        self
+    }
+    pub fn opt_output_timezone(mut self, opt_timezone: Option<TuiLoggerTimezone>) -> Self {
+        if opt_timezone.is_some() {
+            self.format_timezone = opt_timezone;
+        }
+        self
+    }
+    /// Show the timestamp in the given timezone instead of the one set by set_timezone()
+    pub fn output_timezone(mut self, timezone: TuiLoggerTimezone) -> Self {
+        self.format_timezone = Some(timezone);
+        self
+    }
     pub fn opt_output_level(mut self, opt_fmt: Option<Option<TuiLoggerLevelOutput>>) -> Self {
         if let Some(fmt) = opt_fmt {
             self.format_output_level = fmt;
@@ -186,6 +248,47 @@ impl<'b> TuiLoggerWidget<'b> {
         self.format_output_target = enabled;
         self
     }
+    pub fn opt_output_target_max_width(mut self, opt_width: Option<Option<usize>>) -> Self {
+        if let Some(width) = opt_width {
+            self.format_target_max_width = width;
+        }
+        self
+    }
+    /// Abbreviate targets longer than the width, e.g. `a::b::c::name`, `…::name` or `…ame`,
+    /// so long targets do not dominate narrow panes.
+    ///
+    /// Default is None, which shows the targets in full length
+    pub fn output_target_max_width(mut self, width: Option<usize>) -> Self {
+        self.format_target_max_width = width;
+        self
+    }
+    pub fn opt_output_pid(mut self, opt_enabled: Option<bool>) -> Self {
+        if let Some(enabled) = opt_enabled {
+            self.format_output_pid = enabled;
+        }
+        self
+    }
+    /// Enables output of the id of the process, which has logged the event
+    ///
+    /// Default is false
+    pub fn output_pid(mut self, enabled: bool) -> Self {
+        self.format_output_pid = enabled;
+        self
+    }
+    pub fn opt_output_spans(mut self, opt_enabled: Option<bool>) -> Self {
+        if let Some(enabled) = opt_enabled {
+            self.format_output_spans = enabled;
+        }
+        self
+    }
+    /// Enables output of the chain of enclosing tracing spans, e.g. `outer>inner`.
+    /// Events without spans have no such field.
+    ///
+    /// Default is false
+    pub fn output_spans(mut self, enabled: bool) -> Self {
+        self.format_output_spans = enabled;
+        self
+    }
     pub fn opt_output_file(mut self, opt_enabled: Option<bool>) -> Self {
         if let Some(enabled) = opt_enabled {
             self.format_output_file = enabled;
@@ -212,6 +315,125 @@ impl<'b> TuiLoggerWidget<'b> {
         self.format_output_line = enabled;
         self
     }
+    pub fn opt_output_index(mut self, opt_enabled: Option<bool>) -> Self {
+        if let Some(enabled) = opt_enabled {
+            self.format_output_index = enabled;
+        }
+        self
+    }
+    /// Enables output of the event's sequence number as first field.
+    /// The sequence number is unique for each event.
+    ///
+    /// Default is false
+    pub fn output_index(mut self, enabled: bool) -> Self {
+        self.format_output_index = enabled;
+        self
+    }
+    pub fn opt_output_delta(mut self, opt_enabled: Option<bool>) -> Self {
+        if let Some(enabled) = opt_enabled {
+            self.format_output_delta = enabled;
+        }
+        self
+    }
+    /// Enables output of the elapsed time since the previous displayed event,
+    /// e.g. +12.3ms. It is shown after the timestamp.
+    ///
+    /// Default is false
+    pub fn output_delta(mut self, enabled: bool) -> Self {
+        self.format_output_delta = enabled;
+        self
+    }
+    pub fn opt_output_kv_columns(mut self, opt_columns: Option<Vec<(String, usize)>>) -> Self {
+        if let Some(columns) = opt_columns {
+            self.format_kv_columns = columns;
+        }
+        self
+    }
+    /// Show the values of the given keys as columns after the message, e.g.
+    /// ` | request_id=42   | user=admin`. Each key comes with the width of its value.
+    /// The columns are right aligned, as long as they fit into the line.
+    ///
+    /// Default is no columns
+    pub fn output_kv_columns(mut self, columns: Vec<(String, usize)>) -> Self {
+        self.format_kv_columns = columns;
+        self
+    }
+    pub fn opt_output_word_wrap(mut self, opt_enabled: Option<bool>) -> Self {
+        if let Some(enabled) = opt_enabled {
+            self.format_word_wrap = enabled;
+        }
+        self
+    }
+    /// Wrap long lines at word boundaries instead of at the widget's width.
+    /// Words longer than the line are still split.
+    ///
+    /// Default is false
+    pub fn output_word_wrap(mut self, enabled: bool) -> Self {
+        self.format_word_wrap = enabled;
+        self
+    }
+    pub fn opt_output_ansi_styles(mut self, opt_enabled: Option<bool>) -> Self {
+        if let Some(enabled) = opt_enabled {
+            self.format_ansi_styles = enabled;
+        }
+        self
+    }
+    /// Apply the colors and text attributes of ANSI SGR escape sequences in the messages.
+    /// Other escape sequences are removed.
+    /// If disabled, the escape sequences are shown as is.
+    ///
+    /// Default is false
+    pub fn output_ansi_styles(mut self, enabled: bool) -> Self {
+        self.format_ansi_styles = enabled;
+        self
+    }
+    pub fn opt_output_hyperlink(mut self, opt_template: Option<Option<String>>) -> Self {
+        if let Some(template) = opt_template {
+            self.hyperlink_template = template;
+        }
+        self
+    }
+    /// Render the file and line fields as OSC 8 hyperlink, which can be clicked on
+    /// in terminals supporting it. In the template, `{file}` and `{line}` are replaced
+    /// by the event's file and line, e.g. `vscode://file/{file}:{line}`.
+    /// This requires the file to be shown by the standard formatter and a known location.
+    ///
+    /// If called with None, no hyperlinks are rendered.
+    ///
+    /// Default is None
+    pub fn output_hyperlink(mut self, template: Option<String>) -> Self {
+        self.hyperlink_template = template;
+        self
+    }
+    pub fn opt_follow_indicator(mut self, opt_enabled: Option<bool>) -> Self {
+        if let Some(enabled) = opt_enabled {
+            self.follow_indicator = enabled;
+        }
+        self
+    }
+    /// Show `[FOLLOW]` or `[PAUSED]` in the block's title, depending on
+    /// the log view following new events or being in page mode/paused.
+    /// Without a block the indicator is not shown.
+    ///
+    /// Default is false
+    pub fn follow_indicator(mut self, enabled: bool) -> Self {
+        self.follow_indicator = enabled;
+        self
+    }
+    pub fn opt_domain(mut self, opt_domain: Option<String>) -> Self {
+        if let Some(domain) = opt_domain {
+            self.domain = Some(domain);
+        }
+        self
+    }
+    /// Show the events of the log domain set by [`set_log_domain`](crate::set_log_domain)
+    /// instead of the main buffer. If the domain does not exist, the main buffer is shown.
+    ///
+    /// Default is the main buffer
+    pub fn domain(mut self, name: &str) -> Self {
+        self.domain = Some(name.to_string());
+        self
+    }
     pub fn inner_state(mut self, state: Arc<Mutex<TuiWidgetInnerState>>) -> Self {
         self.state = state;
         self
@@ -222,114 +444,495 @@ impl<'b> TuiLoggerWidget<'b> {
     }
 }
 impl<'b> Widget for TuiLoggerWidget<'b> {
-    fn render(mut self, area: Rect, buf: &mut Buffer) {
-        let formatter = match self.logformatter.take() {
-            Some(fmt) => fmt,
-            None => {
-                let fmt = LogStandardFormatter {
-                    style: self.style,
-                    style_error: self.style_error,
-                    style_warn: self.style_warn,
-                    style_debug: self.style_debug,
-                    style_trace: self.style_trace,
-                    style_info: self.style_info,
-                    format_separator: self.format_separator,
-                    format_timestamp: self.format_timestamp,
-                    format_output_level: self.format_output_level,
-                    format_output_target: self.format_output_target,
-                    format_output_file: self.format_output_file,
-                    format_output_line: self.format_output_line,
-                };
-                Box::new(fmt)
-            }
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.draw(area, buf);
+    }
+}
+#[cfg(feature = "widget-ref")]
+#[cfg_attr(docsrs, doc(cfg(feature = "widget-ref")))]
+impl<'b> WidgetRef for TuiLoggerWidget<'b> {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        self.draw(area, buf);
+    }
+}
+impl<'b> TuiLoggerWidget<'b> {
+    /// The block with the follow indicator, if enabled
+    fn titled_block(&self, following: bool) -> Option<Block<'b>> {
+        let b = self.block.clone()?;
+        if !self.follow_indicator {
+            return Some(b);
+        }
+        let indicator = if following { "[FOLLOW]" } else { "[PAUSED]" };
+        Some(b.title_top(Line::from(indicator).right_aligned()))
+    }
+    fn draw(&self, area: Rect, buf: &mut Buffer) {
+        TUI_LOGGER.move_events_if_manual();
+        let standard = Arc::new(self.standard_formatter());
+        let formatter: Arc<dyn LogFormatter> = match self.logformatter.as_ref() {
+            Some(fmt) => fmt.clone(),
+            None => standard.clone(),
         };
+        // The location of the hyperlinks is only known for the standard formatter
+        let opt_standard = self.logformatter.is_none().then_some(standard.as_ref());
 
         buf.set_style(area, self.style);
-        let list_area = match self.block.take() {
-            Some(b) => {
-                let inner_area = b.inner(area);
-                b.render(area, buf);
-                inner_area
-            }
+        // Both indicators have the same width, so the area does not depend on the state.
+        // The block is rendered after the pending scroll has been applied.
+        let list_area = match self.titled_block(true) {
+            Some(b) => b.inner(area),
             None => area,
         };
         if list_area.width < formatter.min_width() || list_area.height < 1 {
+            let following = self.state.lock().is_following();
+            if let Some(b) = self.titled_block(following) {
+                b.render(area, buf);
+            }
             return;
         }
 
         let mut state = self.state.lock();
+        state.domain.clone_from(&self.domain);
         let la_height = list_area.height as usize;
         let la_left = list_area.left();
         let la_top = list_area.top();
         let la_width = list_area.width as usize;
         //let mut lines: Vec<Line> = vec![];
         let mut lines = CircularBuffer::new(la_height);
-        {
-            state.opt_timestamp_next_page = None;
-            let opt_timestamp_bottom = state.opt_timestamp_bottom;
-            let mut opt_timestamp_prev_page = None;
+        let truncate = state.truncate;
+        let format = |evt: &ExtLogRecord, prev: Option<&ExtLogRecord>| {
+            let mut evt_lines = formatter.format_with_previous(la_width, evt, prev);
+            if truncate {
+                evt_lines.truncate(1);
+            }
+            evt_lines
+        };
+        // The spilled events are only kept in memory while paging through history.
+        // The spill file is read without the logger locked. A scroll beyond the loaded
+        // spilled events is continued, after the next ones have been loaded.
+        let mut scroll_to_top = state.scroll_to_top;
+        let mut scroll_lines = state.scroll_lines + state.scroll_pages * la_height as isize;
+        state.scroll_to_top = false;
+        state.scroll_lines = 0;
+        state.scroll_pages = 0;
+        loop {
+            let paging = state.opt_seq_bottom.is_some() || scroll_to_top || scroll_lines < 0;
+            if paging {
+                let anchor = if scroll_to_top {
+                    Some(0)
+                } else {
+                    state.opt_seq_bottom
+                };
+                let opt_read = self
+                    .store(&mut TUI_LOGGER.inner.lock())
+                    .request_spilled(anchor);
+                if let Some(read) = opt_read {
+                    let events = read.read();
+                    self.store(&mut TUI_LOGGER.inner.lock())
+                        .load_spilled(read, events);
+                }
+            }
             let mut tui_lock = TUI_LOGGER.inner.lock();
-            let mut circular = CircularBuffer::new(10); // MAGIC constant
-            for evt in tui_lock.events.rev_iter() {
-                if let Some(level) = state.config.get(&evt.target) {
-                    if level < evt.level {
-                        continue;
-                    }
-                } else if let Some(level) = state.config.default_display_level {
-                    if level < evt.level {
-                        continue;
+            let store = self.store(&mut tui_lock);
+            if !paging {
+                store.unload_spilled();
+            }
+            let (older_edge, newer_edge) = store.spilled_edges();
+            let view: &TuiWidgetInnerState = &state;
+            let mut opt_seq_bottom = view.opt_seq_bottom;
+            if scroll_to_top {
+                // Page mode with the oldest visible event at the top line:
+                // Collect events from the start, until the page is filled.
+                opt_seq_bottom = None;
+                let mut lines_cnt = 0;
+                let mut prev = None;
+                for evt in store.iter().filter(|evt| view.is_displayed(evt)) {
+                    lines_cnt += format(evt, prev).len();
+                    opt_seq_bottom = Some(evt.seq);
+                    prev = Some(evt);
+                    if lines_cnt >= la_height {
+                        break;
                     }
                 }
-                if state.focus_selected {
-                    if let Some(target) = state.opt_selected_target.as_ref() {
-                        if target != &evt.target {
-                            continue;
+            }
+            // Lines still to be scrolled after loading further spilled events
+            let mut remaining = 0;
+            if scroll_lines != 0 {
+                // Move the bottom line's event by the requested number of lines.
+                // Index 0 is the newest displayed event.
+                let mut displayed =
+                    Displayed::new(store.rev_iter().filter(|evt| view.is_displayed(evt)));
+                if displayed.get(0).is_some() {
+                    let mut idx = match opt_seq_bottom {
+                        Some(seq) => displayed.position(seq),
+                        None => 0,
+                    };
+                    let mut lines_cnt = 0;
+                    if scroll_lines < 0 {
+                        while lines_cnt < scroll_lines.unsigned_abs() {
+                            let Some(evt) = displayed.get(idx) else {
+                                break;
+                            };
+                            let prev = match displayed.get(idx + 1) {
+                                Some(prev) if older_edge.is_none_or(|edge| prev.seq >= edge) => {
+                                    prev
+                                }
+                                _ => {
+                                    if older_edge.is_some() {
+                                        remaining = lines_cnt as isize + scroll_lines;
+                                    }
+                                    break;
+                                }
+                            };
+                            lines_cnt += format(evt, Some(prev)).len();
+                            idx += 1;
+                        }
+                        opt_seq_bottom = displayed.get(idx).map(|evt| evt.seq);
+                    } else {
+                        while lines_cnt < scroll_lines as usize && idx > 0 {
+                            if newer_edge.is_some_and(|edge| {
+                                displayed.get(idx - 1).is_some_and(|evt| evt.seq > edge)
+                            }) {
+                                remaining = scroll_lines - lines_cnt as isize;
+                                break;
+                            }
+                            idx -= 1;
+                            if let Some(evt) = displayed.get(idx) {
+                                lines_cnt += format(evt, displayed.get(idx + 1)).len();
+                            }
                         }
+                        // Reaching the newest event continues to follow new events
+                        opt_seq_bottom = if idx == 0 {
+                            None
+                        } else {
+                            displayed.get(idx).map(|evt| evt.seq)
+                        };
                     }
                 }
+            }
+            if remaining != 0 && opt_seq_bottom.is_some() {
+                state.opt_seq_bottom = opt_seq_bottom;
+                scroll_to_top = false;
+                scroll_lines = remaining;
+                continue;
+            }
+            // Only the events needed for the page are taken from the store.
+            // The anchor itself is kept, so the view returns to it, if the filter is changed back.
+            let opt_seq_anchor = opt_seq_bottom
+                .map(|seq| nearest_displayed(view, store.rev_iter(), seq).unwrap_or(seq));
+            let mut opt_seq_next_page = None;
+            let mut opt_seq_prev_page = None;
+            let mut circular = CircularBuffer::new(10); // MAGIC constant
+            let mut displayed = store
+                .rev_iter()
+                .filter(|evt| view.is_displayed(evt))
+                .peekable();
+            while let Some(evt) = displayed.next() {
                 // Here all filters have been applied,
                 // So check, if user is paging through history
-                if let Some(timestamp) = opt_timestamp_bottom.as_ref() {
-                    if *timestamp < evt.timestamp {
-                        circular.push(evt.timestamp);
+                if let Some(seq) = opt_seq_anchor.as_ref() {
+                    if *seq < evt.seq {
+                        circular.push(evt.seq);
                         continue;
                     }
                 }
                 if !circular.is_empty() {
-                    state.opt_timestamp_next_page = circular.take().first().cloned();
+                    opt_seq_next_page = circular.take().first().cloned();
                 }
-                let mut evt_lines = formatter.format(la_width, evt);
+                let mut evt_lines = format(evt, displayed.peek().copied());
+                let mut opt_hyperlink =
+                    self.hyperlink(opt_standard, evt, displayed.peek().copied());
                 while let Some(line) = evt_lines.pop() {
-                    lines.push(line);
+                    // The file and line fields are only linked in the event's first line
+                    let hyperlink = if evt_lines.is_empty() {
+                        opt_hyperlink.take()
+                    } else {
+                        None
+                    };
+                    lines.push((line, hyperlink));
                 }
                 if lines.len() >= la_height {
                     break;
                 }
-                if opt_timestamp_prev_page.is_none() && lines.len() >= la_height / 2 {
-                    opt_timestamp_prev_page = Some(evt.timestamp);
+                if opt_seq_prev_page.is_none() && lines.len() >= la_height / 2 {
+                    opt_seq_prev_page = Some(evt.seq);
                 }
             }
-            state.opt_timestamp_prev_page = opt_timestamp_prev_page.or(state.opt_timestamp_bottom);
+            state.opt_seq_bottom = opt_seq_bottom;
+            state.opt_seq_next_page = opt_seq_next_page;
+            state.opt_seq_prev_page = opt_seq_prev_page.or(opt_seq_bottom);
+            break;
+        }
+
+        if let Some(b) = self.titled_block(state.is_following()) {
+            b.render(area, buf);
         }
 
         // This apparently ensures, that the log starts at top
-        let offset: u16 = if state.opt_timestamp_bottom.is_none() {
+        let offset: u16 = if state.opt_seq_bottom.is_none() {
             0
         } else {
             let lines_cnt = lines.len();
             std::cmp::max(0, la_height - lines_cnt) as u16
         };
 
-        for (i, line) in lines.iter().rev().take(la_height).enumerate() {
-            line.render(
-                Rect {
-                    x: la_left,
-                    y: la_top + i as u16 + offset,
-                    width: list_area.width,
-                    height: 1,
-                },
-                buf,
-            )
+        for (i, (line, opt_hyperlink)) in lines.iter().rev().take(la_height).enumerate() {
+            let line_area = Rect {
+                x: la_left,
+                y: la_top + i as u16 + offset,
+                width: list_area.width,
+                height: 1,
+            };
+            line.render(line_area, buf);
+            if let Some(hyperlink) = opt_hyperlink {
+                hyperlink.render(line_area, buf);
+            }
+        }
+    }
+}
+
+/// OSC 8 hyperlink for a part of a rendered line
+struct Hyperlink {
+    column: u16,
+    width: u16,
+    url: String,
+}
+impl Hyperlink {
+    /// Wrap the already rendered cells into the escape sequences.
+    /// Two cells are combined into one symbol and the second cell is skipped,
+    /// so the terminal's cursor advances as if the sequences would not be there.
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let start = area.x.saturating_add(self.column);
+        let end = area.right().min(start.saturating_add(self.width));
+        let mut x = start;
+        while x < end {
+            let mut text = String::new();
+            for dx in 0..2.min(end - x) {
+                if let Some(cell) = buf.cell_mut((x + dx, area.y)) {
+                    text.push_str(cell.symbol());
+                    if dx > 0 {
+                        cell.set_skip(true);
+                    }
+                }
+            }
+            if let Some(cell) = buf.cell_mut((x, area.y)) {
+                cell.set_symbol(&format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", self.url, text));
+            }
+            x += 2;
         }
     }
 }
+
+impl<'b> TuiLoggerWidget<'b> {
+    /// The events of the widget's domain or the main buffer
+    fn store<'a>(&self, tli: &'a mut TuiLoggerInner) -> &'a mut EventStore {
+        tli.domain_events(self.domain.as_deref())
+    }
+    fn standard_formatter(&self) -> LogStandardFormatter {
+        LogStandardFormatter {
+            style: self.style,
+            style_error: self.style_error,
+            style_warn: self.style_warn,
+            style_debug: self.style_debug,
+            style_trace: self.style_trace,
+            style_info: self.style_info,
+            format_separator: self.format_separator,
+            format_timestamp: self.format_timestamp.clone(),
+            format_output_level: self.format_output_level,
+            format_output_target: self.format_output_target,
+            format_target_max_width: self.format_target_max_width,
+            format_output_file: self.format_output_file,
+            format_output_line: self.format_output_line,
+            format_word_wrap: self.format_word_wrap,
+            format_ansi_styles: self.format_ansi_styles,
+            format_output_index: self.format_output_index,
+            format_output_delta: self.format_output_delta,
+            format_relative_start: self.format_relative_timestamp.then(start_time),
+            format_timezone: self.format_timezone.unwrap_or_else(timezone),
+            format_output_pid: self.format_output_pid,
+            format_kv_columns: self.format_kv_columns.clone(),
+            format_output_spans: self.format_output_spans,
+        }
+    }
+    /// Link the event's file and line fields in the first line of the standard formatter
+    fn hyperlink(
+        &self,
+        opt_standard: Option<&LogStandardFormatter>,
+        evt: &ExtLogRecord,
+        prev: Option<&ExtLogRecord>,
+    ) -> Option<Hyperlink> {
+        let template = self.hyperlink_template.as_ref()?;
+        // Unknown locations are shown as ?
+        if !self.format_output_file || evt.file.is_empty() || evt.file == "?" {
+            return None;
+        }
+        let columns = opt_standard?.location(evt, prev)?;
+        Some(Hyperlink {
+            column: columns.start as u16,
+            width: columns.len() as u16,
+            url: template
+                .replace("{file}", &evt.file)
+                .replace("{line}", &evt.line.to_string()),
+        })
+    }
+}
+
+/// Page mode's anchored event is the event with the anchor's sequence number
+/// or the newest one before it.
+/// If this event is filtered out, the nearest displayed event is used instead.
+/// On equal distance the older event is preferred.
+///
+/// The events are iterated from newest to oldest and only as far as needed.
+fn nearest_displayed<'a>(
+    state: &TuiWidgetInnerState,
+    events: impl Iterator<Item = &'a ExtLogRecord>,
+    seq: u64,
+) -> Option<u64> {
+    // The nearest displayed event newer than the anchored event and its index
+    let mut opt_newer: Option<(usize, u64)> = None;
+    let mut opt_anchor = None;
+    for (i, evt) in events.enumerate() {
+        match opt_anchor {
+            None if evt.seq > seq => {
+                if state.is_displayed(evt) {
+                    opt_newer = Some((i, evt.seq));
+                }
+            }
+            None => {
+                if state.is_displayed(evt) {
+                    return Some(evt.seq);
+                }
+                opt_anchor = Some(i);
+            }
+            Some(anchor) => {
+                if let Some((newer, newer_seq)) = opt_newer {
+                    if anchor - newer < i - anchor {
+                        return Some(newer_seq);
+                    }
+                }
+                if state.is_displayed(evt) {
+                    return Some(evt.seq);
+                }
+            }
+        }
+    }
+    opt_newer.map(|(_, seq)| seq)
+}
+
+/// The displayed events from newest to oldest, taken from the store only as far as needed
+struct Displayed<'a, I: Iterator<Item = &'a ExtLogRecord>> {
+    events: I,
+    taken: Vec<&'a ExtLogRecord>,
+}
+impl<'a, I: Iterator<Item = &'a ExtLogRecord>> Displayed<'a, I> {
+    fn new(events: I) -> Self {
+        Displayed {
+            events,
+            taken: vec![],
+        }
+    }
+    fn get(&mut self, idx: usize) -> Option<&'a ExtLogRecord> {
+        while self.taken.len() <= idx {
+            self.taken.push(self.events.next()?);
+        }
+        Some(self.taken[idx])
+    }
+    /// Index of the newest event not newer than the sequence number, or of the oldest event
+    fn position(&mut self, seq: u64) -> usize {
+        let mut idx = 0;
+        while let Some(evt) = self.get(idx) {
+            if evt.seq <= seq {
+                return idx;
+            }
+            idx += 1;
+        }
+        idx.saturating_sub(1)
+    }
+}
+
+impl<'b> StatefulWidget for TuiLoggerWidget<'b> {
+    type State = TuiWidgetState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut TuiWidgetState) {
+        Widget::render(self.state(state), area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timestamp;
+    use log::{Level, LevelFilter};
+
+    fn events(shown: &[u64]) -> Vec<ExtLogRecord> {
+        (0..10)
+            .map(|seq| {
+                let target = if shown.contains(&seq) {
+                    "shown"
+                } else {
+                    "hidden"
+                };
+                let mut evt = ExtLogRecord::new(timestamp::now(), Level::Info, target, "msg");
+                evt.seq = seq;
+                evt
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_nearest_displayed() {
+        let mut state = TuiWidgetInnerState::new();
+        state.config.set("hidden", LevelFilter::Off);
+        let events = events(&[0, 4, 9]);
+        let nearest = |seq| nearest_displayed(&state, events.iter().rev(), seq);
+        assert_eq!(nearest(4), Some(4));
+        // On equal distance the older event wins
+        assert_eq!(nearest(2), Some(0));
+        assert_eq!(nearest(3), Some(4));
+        assert_eq!(nearest(6), Some(4));
+        assert_eq!(nearest(7), Some(9));
+        assert_eq!(nearest(20), Some(9));
+        state.config.set("shown", LevelFilter::Off);
+        assert_eq!(nearest_displayed(&state, events.iter().rev(), 4), None);
+    }
+
+    #[test]
+    fn test_hyperlink() {
+        let widget = TuiLoggerWidget::default()
+            .output_timestamp(None)
+            .output_target(false)
+            .output_hyperlink(Some("file://{file}#{line}".to_string()));
+        let standard = widget.standard_formatter();
+        let mut evt = ExtLogRecord::new(timestamp::now(), Level::Info, "app", "see src/main.rs");
+        evt.file = "src/main.rs".to_string();
+        evt.line = 42;
+        let text: String = standard.format(80, &evt)[0]
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert_eq!(text, "INFO :src/main.rs:42:see src/main.rs");
+        let hyperlink = widget.hyperlink(Some(&standard), &evt, None).unwrap();
+        assert_eq!((hyperlink.column, hyperlink.width), (6, 14));
+        assert_eq!(hyperlink.url, "file://src/main.rs#42");
+        // Not for a custom formatter, an unknown location or without the file field
+        assert!(widget.hyperlink(None, &evt, None).is_none());
+        let widget = widget.output_file(false);
+        let standard = widget.standard_formatter();
+        assert!(widget.hyperlink(Some(&standard), &evt, None).is_none());
+        let widget = widget.output_file(true);
+        let standard = widget.standard_formatter();
+        evt.file = "?".to_string();
+        assert!(widget.hyperlink(Some(&standard), &evt, None).is_none());
+    }
+
+    #[test]
+    fn test_displayed_position() {
+        let events = events(&[]);
+        let mut displayed = Displayed::new(events.iter().rev().filter(|evt| evt.seq % 4 == 0));
+        assert_eq!(displayed.position(100), 0);
+        assert_eq!(displayed.position(5), 1);
+        assert_eq!(displayed.taken.len(), 2);
+        assert_eq!(displayed.get(2).map(|evt| evt.seq), Some(0));
+        assert!(displayed.get(3).is_none());
+    }
+}
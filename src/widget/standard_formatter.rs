@@ -1,9 +1,12 @@
+use crate::ansi::parse_ansi;
+use crate::timestamp::{TimeDelta, Timestamp};
 use crate::widget::logformatter::LogFormatter;
-use crate::ExtLogRecord;
 use crate::Style;
-use crate::TuiLoggerLevelOutput;
+use crate::{format_duration, format_elapsed, format_pid, ExtLogRecord};
+use crate::{TuiLoggerLevelOutput, TuiLoggerTimezone};
 use ratatui::text::{Line, Span};
 use std::borrow::Cow;
+use std::ops::Range;
 
 pub struct LogStandardFormatter {
     /// Base style of the widget
@@ -18,8 +21,21 @@ pub struct LogStandardFormatter {
     pub format_timestamp: Option<String>,
     pub format_output_level: Option<TuiLoggerLevelOutput>,
     pub format_output_target: bool,
+    /// Targets longer than this are abbreviated
+    pub format_target_max_width: Option<usize>,
     pub format_output_file: bool,
     pub format_output_line: bool,
+    pub format_word_wrap: bool,
+    pub format_ansi_styles: bool,
+    pub format_output_index: bool,
+    pub format_output_delta: bool,
+    /// If set, the timestamp is shown as time elapsed since then
+    pub format_relative_start: Option<Timestamp>,
+    pub format_timezone: TuiLoggerTimezone,
+    pub format_output_pid: bool,
+    /// Keys with the width of their value, which are shown as columns after the message
+    pub format_kv_columns: Vec<(String, usize)>,
+    pub format_output_spans: bool,
 }
 
 impl LogStandardFormatter {
@@ -28,7 +44,7 @@ impl LogStandardFormatter {
         style: Style,
         indent: usize,
         lines: &mut Vec<Line>,
-        line: &str,
+        chars: &[(char, Style)],
         width: usize,
         with_indent: bool,
     ) {
@@ -38,49 +54,130 @@ impl LogStandardFormatter {
             wrap_len -= indent;
         }
         let space = " ".repeat(indent);
-        while p < line.len() {
-            let linelen = std::cmp::min(wrap_len, line.len() - p);
-            let subline = &line[p..p + linelen];
+        while p < chars.len() {
+            let mut linelen = std::cmp::min(wrap_len, chars.len() - p);
+            if self.format_word_wrap && p + linelen < chars.len() {
+                // Break at the last whitespace, unless the word fills the whole line
+                if let Some(pos) = chars[p..=p + linelen]
+                    .iter()
+                    .rposition(|(ch, _)| ch.is_whitespace())
+                {
+                    if pos > 0 {
+                        linelen = pos;
+                    }
+                }
+            }
 
             let mut spans: Vec<Span> = Vec::new();
             if wrap_len < width {
                 // need indent
                 spans.push(Span {
-                    style: style,
+                    style,
                     content: Cow::Owned(space.to_string()),
                 });
             }
+            // Consecutive characters with same style form one span
+            let mut text = String::new();
+            let mut text_style = style;
+            for (ch, ch_style) in &chars[p..p + linelen] {
+                if *ch_style != text_style && !text.is_empty() {
+                    spans.push(Span {
+                        style: text_style,
+                        content: Cow::Owned(std::mem::take(&mut text)),
+                    });
+                }
+                text_style = *ch_style;
+                text.push(*ch);
+            }
             spans.push(Span {
-                style: style,
-                content: Cow::Owned(subline.to_string()),
+                style: text_style,
+                content: Cow::Owned(text),
             });
             let line = Line::from(spans);
             lines.push(line);
 
             p += linelen;
+            if self.format_word_wrap {
+                // The whitespace at the line break is not shown
+                while p < chars.len() && chars[p].0.is_whitespace() {
+                    p += 1;
+                }
+            }
             // following lines need to be indented
             wrap_len = width - indent;
         }
     }
 }
 
-impl LogFormatter for LogStandardFormatter {
-    fn min_width(&self) -> u16 {
-        9 + 4
+/// Format the elapsed time as e.g. +12.3ms, +1.234s or +2m05s
+fn format_delta(delta: TimeDelta) -> String {
+    let micros = delta.num_microseconds().unwrap_or(i64::MAX);
+    format!("+{}", format_duration(micros))
+}
+
+/// Shorten the target to at most `max_width` characters. First the leading segments of the
+/// path are reduced to their first letter from left to right, e.g. `a::b::gamma::delta`,
+/// then only the last segment is kept as `…::delta` and finally its end as `…elta`.
+fn abbreviate_target(target: &str, max_width: usize) -> Cow<'_, str> {
+    if target.chars().count() <= max_width {
+        return Cow::Borrowed(target);
     }
-    fn format(&self, width: usize, evt: &ExtLogRecord) -> Vec<Line> {
-        let mut lines = Vec::new();
+    let mut segments: Vec<String> = target.split("::").map(str::to_string).collect();
+    let last = segments.len() - 1;
+    for i in 0..last {
+        if let Some(ch) = segments[i].chars().next() {
+            segments[i] = ch.to_string();
+        }
+        let abbreviated = segments.join("::");
+        if abbreviated.chars().count() <= max_width {
+            return Cow::Owned(abbreviated);
+        }
+    }
+    if last > 0 {
+        let abbreviated = format!("…::{}", segments[last]);
+        if abbreviated.chars().count() <= max_width {
+            return Cow::Owned(abbreviated);
+        }
+    }
+    if max_width == 0 {
+        return Cow::Borrowed("");
+    }
+    let len = target.chars().count();
+    let tail: String = target.chars().skip(len + 1 - max_width).collect();
+    Cow::Owned(format!("…{}", tail))
+}
+
+impl LogStandardFormatter {
+    /// The fields before the message and the columns of the file and line fields in it
+    fn header(
+        &self,
+        evt: &ExtLogRecord,
+        prev: Option<&ExtLogRecord>,
+    ) -> (String, Option<Range<usize>>) {
         let mut output = String::new();
-        let (col_style, lev_long, lev_abbr, with_loc) = match evt.level {
-            log::Level::Error => (self.style_error, "ERROR", "E", true),
-            log::Level::Warn => (self.style_warn, "WARN ", "W", true),
-            log::Level::Info => (self.style_info, "INFO ", "I", true),
-            log::Level::Debug => (self.style_debug, "DEBUG", "D", true),
-            log::Level::Trace => (self.style_trace, "TRACE", "T", true),
+        let (lev_long, lev_abbr) = match evt.level {
+            log::Level::Error => ("ERROR", "E"),
+            log::Level::Warn => ("WARN ", "W"),
+            log::Level::Info => ("INFO ", "I"),
+            log::Level::Debug => ("DEBUG", "D"),
+            log::Level::Trace => ("TRACE", "T"),
         };
-        let col_style = col_style.unwrap_or(self.style);
+        if self.format_output_index {
+            output.push_str(&format!("{}", evt.seq));
+            output.push(self.format_separator);
+        }
         if let Some(fmt) = self.format_timestamp.as_ref() {
-            output.push_str(&format!("{}", evt.timestamp.format(fmt)));
+            match self.format_relative_start {
+                Some(start) => output.push_str(&format_elapsed(evt.timestamp.delta(&start))),
+                None => output.push_str(&self.format_timezone.format(&evt.timestamp, fmt)),
+            }
+            output.push(self.format_separator);
+        }
+        if self.format_output_delta {
+            let delta = prev
+                .map(|prev| evt.timestamp.delta(&prev.timestamp))
+                .unwrap_or_default();
+            output.push_str(&format_delta(delta));
             output.push(self.format_separator);
         }
         match &self.format_output_level {
@@ -94,28 +191,251 @@ impl LogFormatter for LogStandardFormatter {
                 output.push(self.format_separator);
             }
         }
-        if self.format_output_target {
-            output.push_str(&evt.target);
+        if self.format_output_pid {
+            output.push_str(&format_pid(evt.pid));
             output.push(self.format_separator);
         }
-        if with_loc {
-            if self.format_output_file {
-                output.push_str(&evt.file);
-                output.push(self.format_separator);
-            }
-            if self.format_output_line {
-                output.push_str(&format!("{}", evt.line));
-                output.push(self.format_separator);
+        if self.format_output_target {
+            match self.format_target_max_width {
+                Some(max_width) => output.push_str(&abbreviate_target(&evt.target, max_width)),
+                None => output.push_str(&evt.target),
             }
+            output.push(self.format_separator);
+        }
+        if self.format_output_spans && !evt.spans.is_empty() {
+            output.push_str(&evt.spans.join(">"));
+            output.push(self.format_separator);
+        }
+        let start = output.chars().count();
+        if self.format_output_file {
+            output.push_str(&evt.file);
+            output.push(self.format_separator);
+        }
+        if self.format_output_line {
+            output.push_str(&format!("{}", evt.line));
+            output.push(self.format_separator);
         }
-        let mut sublines: Vec<&str> = evt.msg.lines().rev().collect();
+        // The location without the trailing separator
+        let end = output.chars().count();
+        let location = (end > start).then(|| start..end - 1);
+        (output, location)
+    }
+    /// The columns of the file and line fields in the event's first line, if shown
+    pub fn location(
+        &self,
+        evt: &ExtLogRecord,
+        prev: Option<&ExtLogRecord>,
+    ) -> Option<Range<usize>> {
+        self.header(evt, prev).1
+    }
+    fn level_style(&self, level: log::Level) -> Style {
+        match level {
+            log::Level::Error => self.style_error,
+            log::Level::Warn => self.style_warn,
+            log::Level::Info => self.style_info,
+            log::Level::Debug => self.style_debug,
+            log::Level::Trace => self.style_trace,
+        }
+        .unwrap_or(self.style)
+    }
+    /// Format the key-value columns, e.g. ` | request_id=42   | user=admin `
+    fn kv_columns(&self, evt: &ExtLogRecord) -> String {
+        let mut columns = String::new();
+        for (key, value_width) in &self.format_kv_columns {
+            let value: String = evt
+                .get_value(key)
+                .unwrap_or("")
+                .chars()
+                .take(*value_width)
+                .collect();
+            columns.push_str(&format!(
+                " | {}={:<width$}",
+                key,
+                value,
+                width = value_width
+            ));
+        }
+        columns
+    }
+}
 
-        output.push_str(sublines.pop().unwrap());
-        self.append_wrapped_line(col_style, 9, &mut lines, &output, width, false);
+impl LogFormatter for LogStandardFormatter {
+    fn min_width(&self) -> u16 {
+        9 + 4
+    }
+    fn format(&self, width: usize, evt: &ExtLogRecord) -> Vec<Line<'_>> {
+        self.format_with_previous(width, evt, None)
+    }
+    fn format_with_previous(
+        &self,
+        width: usize,
+        evt: &ExtLogRecord,
+        prev: Option<&ExtLogRecord>,
+    ) -> Vec<Line<'_>> {
+        let mut lines = Vec::new();
+        let col_style = self.level_style(evt.level);
+        let (output, _) = self.header(evt, prev);
+        let span_summary = evt.span_summary();
+        let evt_msg = span_summary.as_deref().unwrap_or(&evt.msg);
+        let msg: Vec<(char, Style)> = if self.format_ansi_styles {
+            parse_ansi(evt_msg, col_style)
+        } else {
+            evt_msg.chars().map(|ch| (ch, col_style)).collect()
+        };
+        // Split into lines like str::lines()
+        let mut sublines: Vec<&[(char, Style)]> = msg
+            .split(|(ch, _)| *ch == '\n')
+            .map(|line| match line.last() {
+                Some(('\r', _)) => &line[..line.len() - 1],
+                _ => line,
+            })
+            .collect();
+        if sublines.len() > 1 && sublines.last().is_some_and(|line| line.is_empty()) {
+            sublines.pop();
+        }
 
-        for subline in sublines.iter().rev() {
+        let mut first_line: Vec<(char, Style)> = output.chars().map(|ch| (ch, col_style)).collect();
+        first_line.extend_from_slice(sublines[0]);
+        if !self.format_kv_columns.is_empty() {
+            let columns = self.kv_columns(evt);
+            // Right aligned columns line up across events, if they fit into the line
+            let len = first_line.len() + columns.chars().count();
+            if len < width {
+                first_line.resize(first_line.len() + width - len, (' ', col_style));
+            }
+            first_line.extend(columns.chars().map(|ch| (ch, col_style)));
+        }
+        self.append_wrapped_line(col_style, 9, &mut lines, &first_line, width, false);
+
+        for subline in sublines.iter().skip(1) {
             self.append_wrapped_line(col_style, 9, &mut lines, subline, width, true);
         }
         lines
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timestamp;
+
+    fn formatter(format_word_wrap: bool) -> LogStandardFormatter {
+        LogStandardFormatter {
+            style: Style::default(),
+            style_error: None,
+            style_warn: None,
+            style_debug: None,
+            style_trace: None,
+            style_info: None,
+            format_separator: ':',
+            format_timestamp: None,
+            format_output_level: None,
+            format_output_target: false,
+            format_target_max_width: None,
+            format_output_file: false,
+            format_output_line: false,
+            format_word_wrap,
+            format_ansi_styles: false,
+            format_output_index: false,
+            format_output_delta: false,
+            format_relative_start: None,
+            format_timezone: TuiLoggerTimezone::Local,
+            format_output_pid: false,
+            format_kv_columns: vec![],
+            format_output_spans: false,
+        }
+    }
+    fn format(fmt: &LogStandardFormatter, width: usize, msg: &str) -> Vec<String> {
+        let evt = ExtLogRecord {
+            seq: 0,
+            pid: None,
+            key_values: vec![],
+            spans: vec![],
+            span_duration: None,
+            backtrace: None,
+            timestamp: timestamp::now(),
+            level: log::Level::Info,
+            target: "test".to_string(),
+            module_path: None,
+            file: "test.rs".to_string(),
+            line: 1,
+            msg: msg.to_string(),
+        };
+        fmt.format(width, &evt)
+            .iter()
+            .map(|line| line.to_string())
+            .collect()
+    }
+    #[test]
+    fn test_wrap_at_width() {
+        let lines = format(&formatter(false), 12, "hello world, how are you");
+        assert_eq!(
+            lines,
+            vec![
+                "hello world,",
+                "          ho",
+                "         w a",
+                "         re ",
+                "         you"
+            ]
+        );
+    }
+    #[test]
+    fn test_word_wrap() {
+        let lines = format(&formatter(true), 15, "hello world, how are you");
+        assert_eq!(
+            lines,
+            vec![
+                "hello world,",
+                "         how",
+                "         are",
+                "         you"
+            ]
+        );
+    }
+    #[test]
+    fn test_word_wrap_long_word() {
+        let lines = format(&formatter(true), 12, "abcdefghijklmnop qr");
+        assert_eq!(
+            lines,
+            vec!["abcdefghijkl", "         mno", "         p", "         qr"]
+        );
+    }
+    #[test]
+    fn test_kv_columns() {
+        let mut fmt = formatter(false);
+        fmt.format_kv_columns = vec![("id".to_string(), 3), ("user".to_string(), 5)];
+        let evt = ExtLogRecord::new(timestamp::now(), log::Level::Info, "test", "msg")
+            .key_value("user", "administrator")
+            .key_value("id", "7");
+        let lines: Vec<String> = fmt
+            .format(30, &evt)
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        assert_eq!(lines, vec!["msg      | id=7   | user=admin"]);
+    }
+    #[test]
+    fn test_abbreviate_target() {
+        let target = "alpha::beta::gamma::delta";
+        assert_eq!(abbreviate_target(target, 30), target);
+        assert_eq!(abbreviate_target(target, 18), "a::b::gamma::delta");
+        assert_eq!(abbreviate_target(target, 14), "a::b::g::delta");
+        assert_eq!(abbreviate_target(target, 8), "…::delta");
+        assert_eq!(abbreviate_target(target, 4), "…lta");
+        assert_eq!(abbreviate_target("network", 4), "…ork");
+        assert_eq!(abbreviate_target(target, 0), "");
+    }
+    #[test]
+    fn test_format_delta() {
+        assert_eq!(format_delta(TimeDelta::microseconds(12_345)), "+12.3ms");
+        assert_eq!(format_delta(TimeDelta::milliseconds(1_234)), "+1.234s");
+        assert_eq!(format_delta(TimeDelta::seconds(125)), "+2m05s");
+        assert_eq!(format_delta(TimeDelta::milliseconds(-5)), "+0.0ms");
+    }
+    #[test]
+    fn test_wrap_multibyte() {
+        let lines = format(&formatter(false), 12, "äöüäöüäöüäöüäöü");
+        assert_eq!(lines, vec!["äöüäöüäöüäöü", "         äöü"]);
+    }
+}
@@ -0,0 +1,59 @@
+#![cfg(all(feature = "gzip", not(feature = "tracing-support")))]
+use log::*;
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+use tui_logger::*;
+
+fn render(state: &TuiWidgetState) -> Vec<String> {
+    let area = Rect::new(0, 0, 20, 4);
+    let mut buf = Buffer::empty(area);
+    TuiLoggerWidget::default()
+        .output_timestamp(None)
+        .output_level(None)
+        .output_target(false)
+        .output_file(false)
+        .output_line(false)
+        .state(state)
+        .render(area, &mut buf);
+    (0..area.height)
+        .map(|y| {
+            let line: String = (0..area.width).map(|x| buf[(x, y)].symbol()).collect();
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn test_compressed_history() {
+    init_logger_no_mover(LevelFilter::Trace).unwrap();
+    set_buffer_depth(10);
+    set_compressed_history(16, usize::MAX);
+    for i in 0..1000 {
+        info!("event {}", i);
+    }
+    move_events();
+    assert_eq!(stats().buffered_events, 10);
+
+    // The evicted events are decompressed on scrolling back
+    let mut state = TuiWidgetState::new();
+    state.transition(TuiWidgetEvent::TopKey);
+    assert_eq!(render(&state), ["event 0", "event 1", "event 2", "event 3"]);
+    state.scroll_lines(500);
+    assert_eq!(
+        render(&state),
+        ["event 500", "event 501", "event 502", "event 503"]
+    );
+    state.transition(TuiWidgetEvent::BottomKey);
+    assert_eq!(
+        render(&state),
+        ["event 996", "event 997", "event 998", "event 999"]
+    );
+
+    // The oldest blocks are dropped beyond the size limit
+    set_compressed_history(16, 1);
+    for i in 1000..1100 {
+        info!("event {}", i);
+    }
+    move_events();
+    state.transition(TuiWidgetEvent::TopKey);
+    assert_ne!(render(&state)[0], "event 0");
+}
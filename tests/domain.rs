@@ -0,0 +1,62 @@
+#![cfg(not(feature = "tracing-support"))]
+use log::*;
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+use tui_logger::*;
+
+fn render(widget: TuiLoggerWidget) -> Vec<String> {
+    let area = Rect::new(0, 0, 20, 3);
+    let mut buf = Buffer::empty(area);
+    widget
+        .output_timestamp(None)
+        .output_level(None)
+        .output_target(false)
+        .output_file(false)
+        .output_line(false)
+        .render(area, &mut buf);
+    (0..area.height)
+        .map(|y| {
+            let line: String = (0..area.width).map(|x| buf[(x, y)].symbol()).collect();
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn test_domain() {
+    init_logger_no_mover(LevelFilter::Trace).unwrap();
+    set_log_domain("net", &["net::*"], 10);
+    info!(target: "app", "app event");
+    info!(target: "net::http", "request 1");
+    move_events();
+    let mut state = TuiWidgetState::new();
+    assert_eq!(
+        render(TuiLoggerWidget::default().state(&state)),
+        ["app event", "", ""]
+    );
+    assert_eq!(
+        render(TuiLoggerWidget::default().domain("net").state(&state)),
+        ["request 1", "", ""]
+    );
+
+    // Pausing freezes the view of the domain at its newest event, too
+    state.transition(TuiWidgetEvent::PauseKey);
+    info!(target: "net::http", "request 2");
+    move_events();
+    assert_eq!(
+        render(TuiLoggerWidget::default().domain("net").state(&state)),
+        ["request 1", "", ""]
+    );
+    state.transition(TuiWidgetEvent::PauseKey);
+    assert_eq!(
+        render(TuiLoggerWidget::default().domain("net").state(&state)),
+        ["request 1", "request 2", ""]
+    );
+
+    // The events of a target are cleared from the domain, too
+    info!(target: "net::dns", "lookup");
+    clear_events_for_target("net::http");
+    assert_eq!(
+        render(TuiLoggerWidget::default().domain("net").state(&state)),
+        ["lookup", "", ""]
+    );
+}
@@ -0,0 +1,248 @@
+//! Access to the events and targets by the application. The tests share the global logger,
+//! so they run serialized.
+#![cfg(not(feature = "tracing-support"))]
+use std::sync::{Arc, Mutex, MutexGuard, Once};
+
+use log::*;
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+use tui_logger::*;
+
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn setup() -> MutexGuard<'static, ()> {
+    static INIT: Once = Once::new();
+    let guard = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    INIT.call_once(|| init_logger_no_mover(LevelFilter::Trace).unwrap());
+    reset_for_tests();
+    set_default_level(LevelFilter::Trace);
+    guard
+}
+
+fn messages(events: &[Arc<ExtLogRecord>]) -> Vec<String> {
+    events.iter().map(|evt| evt.msg.clone()).collect()
+}
+
+#[test]
+fn test_event_hook() {
+    let _guard = setup();
+    let received = Arc::new(Mutex::new(vec![]));
+    let hook_received = received.clone();
+    let id = add_event_hook(Box::new(move |evt| {
+        hook_received.lock().unwrap().push(evt.msg.clone())
+    }));
+    info!(target: "app", "first");
+    error!(target: "app", "second");
+    move_events();
+    assert_eq!(*received.lock().unwrap(), ["first", "second"]);
+
+    // After removal, the hook is no longer called
+    assert!(remove_sink(id));
+    assert!(!remove_sink(id));
+    info!(target: "app", "third");
+    move_events();
+    assert_eq!(received.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn test_event_receiver() {
+    let _guard = setup();
+    info!(target: "app", "before");
+    move_events();
+    // The receiver gets only the events from its creation on
+    let receiver = event_receiver();
+    info!(target: "app", "first");
+    warn!(target: "app", "second");
+    move_events();
+    let evt = receiver.try_recv().unwrap();
+    assert_eq!((evt.level, evt.msg.as_str()), (Level::Info, "first"));
+    let evt = receiver.try_recv().unwrap();
+    assert_eq!((evt.level, evt.msg.as_str()), (Level::Warn, "second"));
+    assert!(receiver.try_recv().is_err());
+
+    // A dropped receiver does not disturb the capture
+    drop(receiver);
+    info!(target: "app", "after drop");
+    move_events();
+    assert_eq!(
+        messages(&events_snapshot(|_| true)),
+        ["before", "first", "second", "after drop"]
+    );
+}
+
+#[test]
+fn test_events_since() {
+    let _guard = setup();
+    let (events, next) = events_since(0);
+    assert!(events.is_empty());
+    assert_eq!(next, 0);
+
+    info!(target: "app", "a");
+    info!(target: "app", "b");
+    // Events are only returned after they have been moved
+    assert!(events_since(next).0.is_empty());
+    move_events();
+    let (events, next) = events_since(next);
+    assert_eq!(messages(&events), ["a", "b"]);
+    assert_eq!(next, 2);
+
+    // Polling again returns only the new events
+    let (events, next) = events_since(next);
+    assert!(events.is_empty());
+    info!(target: "app", "c");
+    move_events();
+    let (events, next) = events_since(next);
+    assert_eq!(messages(&events), ["c"]);
+    assert_eq!(events[0].seq, 2);
+    assert_eq!(next, 3);
+}
+
+#[test]
+fn test_targets() {
+    let _guard = setup();
+    set_level_for_target("net", LevelFilter::Warn);
+    info!(target: "ui", "shown");
+    info!(target: "db", "shown");
+    move_events();
+    // Sorted by name, with the own levelfilter only for targets set explicitly
+    assert_eq!(
+        targets(),
+        [
+            ("db".to_string(), LevelFilter::Trace, None),
+            (
+                "net".to_string(),
+                LevelFilter::Warn,
+                Some(LevelFilter::Warn)
+            ),
+            ("ui".to_string(), LevelFilter::Trace, None),
+        ]
+    );
+}
+
+#[test]
+fn test_remove_targets() {
+    let _guard = setup();
+    set_level_for_target("net", LevelFilter::Off);
+    set_level_for_target("db", LevelFilter::Off);
+    set_level_for_target("ui", LevelFilter::Off);
+    info!(target: "net", "dropped");
+    move_events();
+    assert!(events_snapshot(|_| true).is_empty());
+
+    // A removed target is captured again with the default level
+    remove_target("net");
+    info!(target: "net", "net 1");
+    move_events();
+    assert_eq!(messages(&events_snapshot(|_| true)), ["net 1"]);
+    let names: Vec<String> = targets().into_iter().map(|(name, _, _)| name).collect();
+    assert_eq!(names, ["db", "net", "ui"]);
+    assert_eq!(targets()[1], ("net".to_string(), LevelFilter::Trace, None));
+
+    retain_targets(|target, _| target != "db");
+    info!(target: "db", "db 1");
+    info!(target: "ui", "dropped");
+    move_events();
+    assert_eq!(messages(&events_snapshot(|_| true)), ["net 1", "db 1"]);
+
+    clear_targets();
+    info!(target: "ui", "ui 1");
+    move_events();
+    assert_eq!(
+        messages(&events_snapshot(|_| true)),
+        ["net 1", "db 1", "ui 1"]
+    );
+    let names: Vec<String> = targets().into_iter().map(|(name, _, _)| name).collect();
+    assert_eq!(names, ["ui"]);
+}
+
+#[test]
+fn test_target_selector() {
+    let _guard = setup();
+    // Rendered lines of the target selector without the level columns
+    let render = |state: &TuiWidgetState| {
+        let area = Rect::new(0, 0, 20, 4);
+        let mut buf = Buffer::empty(area);
+        TuiLoggerTargetWidget::default()
+            .state(state)
+            .render(area, &mut buf);
+        (0..area.height)
+            .map(|y| {
+                let line: String = (6..area.width).map(|x| buf[(x, y)].symbol()).collect();
+                line.trim_end().to_string()
+            })
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+    };
+    let state = TuiWidgetState::new().set_level_for_target("not_yet", LevelFilter::Debug);
+    set_level_for_target("net", LevelFilter::Warn);
+    info!(target: "app", "a");
+    move_events();
+    assert_eq!(render(&state), ["app", "net", "not_yet"]);
+
+    // Targets removed from the logger are hidden, until they log again
+    remove_target("net");
+    clear_targets();
+    assert_eq!(render(&state), ["not_yet"]);
+    info!(target: "net", "b");
+    move_events();
+    assert_eq!(render(&state), ["net", "not_yet"]);
+}
+
+#[test]
+fn test_env_filter() {
+    let _guard = setup();
+    set_default_level(LevelFilter::Info);
+    std::env::set_var("RUST_LOG", "warn,my_crate=debug");
+    reload_env_filter();
+    debug!(target: "my_crate", "crate");
+    debug!(target: "my_crate::sub", "sub");
+    trace!(target: "my_crate::sub", "dropped");
+    debug!(target: "my_crate_ext", "dropped");
+    info!(target: "other", "dropped");
+    move_events();
+    assert_eq!(messages(&events_snapshot(|_| true)), ["crate", "sub"]);
+    // The target below the directive follows it without own levelfilter
+    assert!(targets().contains(&("my_crate::sub".to_string(), LevelFilter::Debug, None)));
+
+    // Reloading the changed filter applies to the target below, too
+    std::env::set_var("RUST_LOG", "warn,my_crate=error");
+    reload_env_filter();
+    warn!(target: "my_crate::sub", "dropped");
+    error!(target: "my_crate::sub", "sub error");
+    move_events();
+    assert_eq!(
+        messages(&events_snapshot(|_| true)),
+        ["crate", "sub", "sub error"]
+    );
+    assert!(targets().contains(&("my_crate::sub".to_string(), LevelFilter::Error, None)));
+    std::env::remove_var("RUST_LOG");
+    reload_env_filter();
+    assert!(targets().contains(&("my_crate::sub".to_string(), LevelFilter::Warn, None)));
+}
+
+#[test]
+fn test_follow_indicator() {
+    let _guard = setup();
+    let render = |state: &TuiWidgetState| {
+        let area = Rect::new(0, 0, 80, 4);
+        let mut buf = Buffer::empty(area);
+        TuiLoggerWidget::default()
+            .block(ratatui::widgets::Block::bordered())
+            .follow_indicator(true)
+            .state(state)
+            .render(area, &mut buf);
+        (0..area.width)
+            .map(|x| buf[(x, 0)].symbol())
+            .collect::<String>()
+    };
+    for i in 0..5 {
+        info!(target: "app", "event {}", i);
+    }
+    move_events();
+    let mut state = TuiWidgetState::new();
+    assert!(render(&state).contains("[FOLLOW]"));
+    // The indicator shows the scroll already in the frame applying it
+    state.scroll_lines(-1);
+    assert!(render(&state).contains("[PAUSED]"));
+    state.transition(TuiWidgetEvent::BottomKey);
+    assert!(render(&state).contains("[FOLLOW]"));
+}
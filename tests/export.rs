@@ -0,0 +1,116 @@
+//! Export and access of the buffered events including the log domains.
+//! The tests share the global logger, so they run serialized.
+#![cfg(not(feature = "tracing-support"))]
+use std::fs;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex, MutexGuard, Once};
+
+use log::*;
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+use tui_logger::*;
+
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn setup() -> MutexGuard<'static, ()> {
+    static INIT: Once = Once::new();
+    let guard = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    INIT.call_once(|| init_logger_no_mover(LevelFilter::Trace).unwrap());
+    reset_for_tests();
+    set_default_level(LevelFilter::Trace);
+    guard
+}
+
+/// The events "a" and "c" go to the main buffer, "b" to the domain "net"
+fn log_with_domain() {
+    set_log_domain("net", &["net::*"], 100);
+    info!(target: "app", "a");
+    info!(target: "net::http", "b");
+    info!(target: "app", "c");
+    move_events();
+}
+
+fn messages(events: &[Arc<ExtLogRecord>]) -> Vec<String> {
+    events.iter().map(|evt| evt.msg.clone()).collect()
+}
+
+/// In-memory writer for the dump
+#[derive(Clone, Default)]
+struct Shared(Arc<Mutex<Vec<u8>>>);
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Messages in the last column of the exported CSV file
+fn csv_messages(path: &str) -> Vec<String> {
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .skip(1)
+        .map(|line| line.rsplit(',').next().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_events_of_domains() {
+    let _guard = setup();
+    log_with_domain();
+    // The events of all buffers are returned in the order of capture
+    assert_eq!(messages(&events_snapshot(|_| true)), ["a", "b", "c"]);
+    assert_eq!(messages(&events_snapshot(|evt| evt.target != "app")), ["b"]);
+    let (events, next) = events_since(1);
+    assert_eq!(messages(&events), ["b", "c"]);
+    assert_eq!(next, 3);
+    let seqs = with_events(|events| events.map(|evt| evt.seq).collect::<Vec<_>>());
+    assert_eq!(seqs, [0, 1, 2]);
+    assert_eq!(stats().buffered_events, 3);
+}
+
+#[test]
+fn test_dump_buffer() {
+    let _guard = setup();
+    log_with_domain();
+    let writer = Shared::default();
+    let file = TuiLoggerFile::with_writer(writer.clone())
+        .output_timestamp(None)
+        .output_level(None)
+        .output_target(false);
+    dump_buffer(file).unwrap();
+    assert_eq!(*writer.0.lock().unwrap(), b"a\nb\nc\n");
+}
+
+#[test]
+fn test_export_csv() {
+    let _guard = setup();
+    log_with_domain();
+    let dir = std::env::temp_dir();
+    let path = dir.join(format!("tui-logger-export-{}.csv", std::process::id()));
+    let path = path.to_str().unwrap();
+    export_csv(path, None).unwrap();
+    assert_eq!(csv_messages(path), ["a", "b", "c"]);
+
+    // With a state, the events of the buffer shown by its widget are exported
+    let render = |state: &TuiWidgetState, domain: Option<&str>| {
+        let area = Rect::new(0, 0, 20, 4);
+        let widget = TuiLoggerWidget::default().state(state);
+        let widget = match domain {
+            Some(name) => widget.domain(name),
+            None => widget,
+        };
+        widget.render(area, &mut Buffer::empty(area));
+    };
+    let main_state = TuiWidgetState::new();
+    render(&main_state, None);
+    export_csv(path, Some(&main_state)).unwrap();
+    assert_eq!(csv_messages(path), ["a", "c"]);
+    let domain_state = TuiWidgetState::new();
+    render(&domain_state, Some("net"));
+    export_csv(path, Some(&domain_state)).unwrap();
+    assert_eq!(csv_messages(path), ["b"]);
+    fs::remove_file(path).unwrap();
+}
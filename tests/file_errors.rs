@@ -0,0 +1,163 @@
+//! The error policies of the file dump and the syslog sink. The warnings are logged, so the tests share
+//! the global logger and run serialized.
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Once};
+
+use log::*;
+use tui_logger::*;
+
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn setup() -> MutexGuard<'static, ()> {
+    static INIT: Once = Once::new();
+    let guard = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    INIT.call_once(|| init_logger_no_mover(LevelFilter::Trace).unwrap());
+    reset_for_tests();
+    set_default_level(LevelFilter::Trace);
+    guard
+}
+
+/// Writer failing while `failing` is set, and counting the attempted writes
+#[derive(Clone, Default)]
+struct Failing {
+    failing: Arc<AtomicBool>,
+    attempts: Arc<AtomicUsize>,
+}
+impl Write for Failing {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+        if self.failing.load(Ordering::Relaxed) {
+            Err(io::Error::other("disk full"))
+        } else {
+            Ok(buf.len())
+        }
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+impl Failing {
+    fn set_failing(&self, failing: bool) {
+        self.failing.store(failing, Ordering::Relaxed);
+    }
+    fn attempts(&self) -> usize {
+        self.attempts.load(Ordering::Relaxed)
+    }
+}
+
+fn write(file: &mut TuiLoggerFile, count: usize) {
+    for _ in 0..count {
+        file.write(&ExtLogRecord::new(
+            Timestamp::default(),
+            Level::Info,
+            "app",
+            "event",
+        ));
+    }
+}
+
+/// The warnings of the file dump
+fn warnings() -> Vec<String> {
+    move_events();
+    events_snapshot(|evt| evt.target == "TuiLogger")
+        .iter()
+        .map(|evt| evt.msg.clone())
+        .collect()
+}
+
+#[test]
+fn test_ignore() {
+    let _guard = setup();
+    let writer = Failing::default();
+    let mut file = TuiLoggerFile::with_writer(writer.clone());
+    writer.set_failing(true);
+    write(&mut file, 3);
+    assert_eq!(file.failures, 3);
+    assert_eq!(writer.attempts(), 3);
+    assert!(warnings().is_empty());
+    // A successful write resets the count of consecutive errors
+    writer.set_failing(false);
+    write(&mut file, 1);
+    assert_eq!(file.failures, 0);
+}
+
+#[test]
+fn test_warn() {
+    let _guard = setup();
+    let writer = Failing::default();
+    let mut file =
+        TuiLoggerFile::with_writer(writer.clone()).on_error(TuiLoggerFileErrorPolicy::Warn);
+    writer.set_failing(true);
+    write(&mut file, 3);
+    // Only the first of consecutive errors is reported
+    assert_eq!(warnings().len(), 1);
+    assert!(warnings()[0].contains("failed: disk full"));
+    writer.set_failing(false);
+    write(&mut file, 1);
+    writer.set_failing(true);
+    write(&mut file, 2);
+    assert_eq!(warnings().len(), 2);
+    assert_eq!(file.failures, 2);
+}
+
+#[test]
+fn test_disable() {
+    let _guard = setup();
+    let writer = Failing::default();
+    let mut file =
+        TuiLoggerFile::with_writer(writer.clone()).on_error(TuiLoggerFileErrorPolicy::Disable(2));
+    writer.set_failing(true);
+    write(&mut file, 1);
+    assert!(warnings().is_empty());
+    write(&mut file, 1);
+    let warnings = warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("disabled after 2 errors: disk full"));
+
+    // Nothing is written anymore, even after the writer has recovered
+    writer.set_failing(false);
+    write(&mut file, 3);
+    file.flush();
+    assert_eq!(writer.attempts(), 2);
+    assert_eq!(file.failures, 2);
+}
+
+#[test]
+fn test_callback() {
+    let _guard = setup();
+    let writer = Failing::default();
+    let errors = Arc::new(Mutex::new(vec![]));
+    let callback_errors = errors.clone();
+    let mut file =
+        TuiLoggerFile::with_writer(writer.clone()).on_error(TuiLoggerFileErrorPolicy::Callback(
+            Box::new(move |err| callback_errors.lock().unwrap().push(err.to_string())),
+        ));
+    writer.set_failing(true);
+    write(&mut file, 2);
+    assert_eq!(*errors.lock().unwrap(), ["disk full", "disk full"]);
+    assert!(warnings().is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_syslog_disable() {
+    let _guard = setup();
+    let path = std::env::temp_dir().join(format!("tui-logger-syslog-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let collector = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+    let mut syslog = TuiLoggerSyslog::new_unix(path.to_str().unwrap())
+        .unwrap()
+        .on_error(TuiLoggerFileErrorPolicy::Disable(2));
+    // The sends fail after the collector has gone away
+    drop(collector);
+    std::fs::remove_file(&path).unwrap();
+    let event = ExtLogRecord::new(Timestamp::default(), Level::Info, "app", "event");
+    for _ in 0..3 {
+        syslog.write(&event);
+    }
+    assert_eq!(syslog.failures, 2);
+    let warnings = warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("Writing to syslog disabled after 2 errors"));
+}
@@ -0,0 +1,156 @@
+//! The hot buffer moved manually. The tests share the global logger, so they run serialized.
+#![cfg(not(feature = "tracing-support"))]
+use std::sync::{Arc, Mutex, MutexGuard, Once};
+use std::thread;
+
+use log::*;
+use tui_logger::*;
+
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn setup() -> MutexGuard<'static, ()> {
+    static INIT: Once = Once::new();
+    let guard = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    INIT.call_once(|| init_logger_no_mover(LevelFilter::Trace).unwrap());
+    reset_for_tests();
+    set_default_level(LevelFilter::Trace);
+    guard
+}
+
+fn messages() -> Vec<String> {
+    events_snapshot(|_| true)
+        .iter()
+        .map(|evt| evt.msg.clone())
+        .collect()
+}
+
+#[test]
+fn test_threads_and_shards() {
+    let _guard = setup();
+    set_hot_buffer_depth(1000);
+    set_hot_buffer_shards(4);
+    move_events();
+    let threads: Vec<_> = (0..4)
+        .map(|t| {
+            thread::spawn(move || {
+                for n in 0..200 {
+                    info!(target: "app", "{} {}", t, n);
+                }
+            })
+        })
+        .collect();
+    for handle in threads {
+        handle.join().unwrap();
+    }
+    let summary = move_events();
+    assert_eq!(
+        summary,
+        TuiLoggerMoveSummary {
+            moved: 800,
+            lost: 0
+        }
+    );
+
+    // Each thread's events keep their order, and the sequence numbers increase
+    let events = events_snapshot(|_| true);
+    assert_eq!(events.len(), 800);
+    assert!(events.windows(2).all(|pair| pair[0].seq < pair[1].seq));
+    assert!(events
+        .windows(2)
+        .all(|pair| pair[0].timestamp <= pair[1].timestamp));
+    for t in 0..4 {
+        let prefix = format!("{} ", t);
+        let numbers: Vec<usize> = events
+            .iter()
+            .filter_map(|evt| evt.msg.strip_prefix(&prefix))
+            .map(|n| n.parse().unwrap())
+            .collect();
+        assert_eq!(numbers, (0..200).collect::<Vec<_>>());
+    }
+
+    let stats = stats();
+    assert_eq!(stats.total_events, 800);
+    assert_eq!(stats.buffered_events, 800);
+    assert_eq!(stats.lost_events, 0);
+    assert_eq!(stats.level_count(Level::Info), 800);
+    assert_eq!(stats.level_count(Level::Warn), 0);
+    assert!(stats.hot_buffer_high_water >= 800);
+}
+
+#[test]
+fn test_overflow() {
+    let _guard = setup();
+    set_hot_buffer_depth(10);
+    move_events();
+    let reports = Arc::new(Mutex::new(vec![]));
+    let hook_reports = reports.clone();
+    set_lost_events_hook(move |lost| hook_reports.lock().unwrap().push(*lost));
+    for n in 0..25 {
+        info!(target: "app", "event {}", n);
+    }
+    let summary = move_events();
+    assert_eq!(
+        summary,
+        TuiLoggerMoveSummary {
+            moved: 10,
+            lost: 15
+        }
+    );
+
+    // The oldest events are lost, which is reported before the recorded ones
+    let messages = messages();
+    assert_eq!(
+        messages[0],
+        "There have been 15 events lost, 10 recorded out of 25"
+    );
+    assert_eq!(messages[1], "event 15");
+    assert_eq!(messages[10], "event 24");
+    {
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!((reports[0].lost, reports[0].recorded), (15, 10));
+        assert!(reports[0].from <= reports[0].to);
+    }
+
+    let stats = stats();
+    assert_eq!(stats.total_events, 25);
+    assert_eq!(stats.lost_events, 15);
+    assert_eq!(stats.buffered_events, 11);
+    assert_eq!(stats.level_count(Level::Warn), 1);
+
+    // Without overflow, the hook is not called
+    clear_lost_events_hook();
+    info!(target: "app", "event 25");
+    assert_eq!(move_events(), TuiLoggerMoveSummary { moved: 1, lost: 0 });
+    assert_eq!(reports.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn test_reset_for_tests() {
+    let _guard = setup();
+    set_buffer_depth(5);
+    set_level_for_target("app", LevelFilter::Warn);
+    let received = Arc::new(Mutex::new(0));
+    let hook_received = received.clone();
+    add_event_hook(Box::new(move |_| *hook_received.lock().unwrap() += 1));
+    warn!(target: "app", "before reset");
+    info!(target: "app", "filtered");
+    move_events();
+    assert_eq!(messages(), ["before reset"]);
+    assert_eq!(*received.lock().unwrap(), 1);
+
+    // Events, levels, sinks and counters are gone
+    reset_for_tests();
+    assert_eq!(stats(), TuiLoggerStats::default());
+    assert!(targets().is_empty());
+    info!(target: "app", "after reset");
+    move_events();
+    assert_eq!(messages(), ["after reset"]);
+    assert_eq!(*received.lock().unwrap(), 1);
+    // The buffer depth is back at its default
+    for n in 0..10 {
+        info!(target: "app", "event {}", n);
+    }
+    move_events();
+    assert_eq!(stats().buffered_events, 11);
+}
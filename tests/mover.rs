@@ -0,0 +1,88 @@
+//! The mover thread moves the events without calls of move_events().
+#![cfg(not(feature = "tracing-support"))]
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::*;
+use tui_logger::*;
+
+/// Sink recording the messages. It panics on the message "boom".
+#[derive(Clone, Default)]
+struct Recorder {
+    messages: Arc<Mutex<Vec<String>>>,
+    flushed: Arc<Mutex<bool>>,
+}
+impl LogSink for Recorder {
+    fn write(&mut self, log_entry: &ExtLogRecord) {
+        if log_entry.msg == "boom" {
+            panic!("sink failed");
+        }
+        self.messages.lock().unwrap().push(log_entry.msg.clone());
+    }
+    fn flush(&mut self) {
+        *self.flushed.lock().unwrap() = true;
+    }
+}
+impl Recorder {
+    /// Wait for the mover thread to write the number of messages
+    fn wait_for(&self, count: usize) -> Vec<String> {
+        let start = Instant::now();
+        while self.messages.lock().unwrap().len() < count
+            && start.elapsed() < Duration::from_secs(5)
+        {
+            thread::sleep(Duration::from_millis(1));
+        }
+        self.messages.lock().unwrap().clone()
+    }
+}
+
+#[test]
+fn test_mover_thread() {
+    let options = TuiLoggerOptions::default().max_move_latency(Duration::from_millis(5));
+    init_logger_with(LevelFilter::Trace, options).unwrap();
+    set_default_level(LevelFilter::Trace);
+    let recorder = Recorder::default();
+    add_sink(recorder.clone());
+
+    // The events of all threads are moved by the mover thread
+    let threads: Vec<_> = (0..4)
+        .map(|t| thread::spawn(move || info!(target: "app", "thread {}", t)))
+        .collect();
+    for handle in threads {
+        handle.join().unwrap();
+    }
+    let mut messages = recorder.wait_for(4);
+    messages.sort();
+    assert_eq!(messages, ["thread 0", "thread 1", "thread 2", "thread 3"]);
+
+    // A panic of a sink is reported, and the mover thread keeps running
+    error!(target: "app", "boom");
+    let start = Instant::now();
+    let report = loop {
+        let reports = events_snapshot(|evt| evt.target == "TuiLogger");
+        if let Some(report) = reports.first() {
+            break report.clone();
+        }
+        assert!(start.elapsed() < Duration::from_secs(5), "no report");
+        thread::sleep(Duration::from_millis(1));
+    };
+    assert_eq!(report.level, Level::Error);
+    assert!(report.msg.contains("sink failed"));
+    info!(target: "app", "after panic");
+    assert_eq!(recorder.wait_for(5)[4], "after panic");
+
+    // Shutdown writes the pending events, flushes and removes the sinks and the spill file
+    let spill_path = std::env::temp_dir().join(format!("tui-logger-mover-{}", std::process::id()));
+    set_spill_file(spill_path.to_str()).unwrap();
+    assert!(spill_path.exists());
+    info!(target: "app", "before shutdown");
+    shutdown();
+    assert!(!spill_path.exists());
+    assert_eq!(recorder.messages.lock().unwrap()[5], "before shutdown");
+    assert!(*recorder.flushed.lock().unwrap());
+    info!(target: "app", "after shutdown");
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(recorder.messages.lock().unwrap().len(), 6);
+    assert!(events_snapshot(|evt| evt.msg == "after shutdown").is_empty());
+}